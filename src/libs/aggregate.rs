@@ -0,0 +1,198 @@
+use std::net::Ipv4Addr;
+
+use crate::libs::calc_ip::{AddressCount, IpCalculatorError, IpFamily, Subnet};
+use crate::libs::ip_set::{list_items, IpSet};
+use crate::libs::json::{Json, ToJson};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary<A: IpFamily = Ipv4Addr> {
+    pub subnet: Subnet<A>,
+    pub extra_addresses: AddressCount,
+}
+
+impl<A: IpFamily> ToJson for Summary<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("cidr", Json::string(self.subnet)),
+            ("extra_addresses", self.extra_addresses.into()),
+        ])
+    }
+}
+
+pub fn parse_prefix_list<A: IpFamily>(input: &str) -> Result<Vec<Subnet<A>>, IpCalculatorError> {
+    list_items(input)
+        .map(|item| match A::from_str(item) {
+            Ok(ip) => Subnet::new(ip, A::BITS),
+            Err(_) => item.parse(),
+        })
+        .collect()
+}
+
+pub fn supernet<A: IpFamily>(prefixes: &[Subnet<A>]) -> Option<Subnet<A>> {
+    let (first, rest) = prefixes.split_first()?;
+    Some(rest.iter().fold(*first, |acc, subnet| acc.common_supernet(subnet)))
+}
+
+pub fn supernet_extra_addresses<A: IpFamily>(supernet: &Subnet<A>, prefixes: &[Subnet<A>]) -> AddressCount {
+    let covered = prefixes.iter().copied().collect::<IpSet<A>>().len();
+    supernet.address_total().saturating_sub(covered)
+}
+
+pub fn aggregate<A: IpFamily>(prefixes: &[Subnet<A>]) -> Vec<Subnet<A>> {
+    prefixes.iter().copied().collect::<IpSet<A>>().to_subnets()
+}
+
+pub fn summarize<A: IpFamily>(
+    prefixes: &[Subnet<A>],
+    max_entries: usize,
+) -> Result<Vec<Summary<A>>, IpCalculatorError> {
+    if max_entries == 0 {
+        return Err(IpCalculatorError::ArgumentsError(
+            "The maximum number of summary entries must be greater than 0".to_string(),
+        ));
+    }
+
+    let mut entries: Vec<Summary<A>> = aggregate(prefixes)
+        .into_iter()
+        .map(|subnet| Summary { subnet, extra_addresses: AddressCount::ZERO })
+        .collect();
+
+    while entries.len() > max_entries {
+        let mut best: Option<(AddressCount, usize, usize, Subnet<A>)> = None;
+
+        for i in 0..entries.len() - 1 {
+            let supernet = entries[i].subnet.common_supernet(&entries[i + 1].subnet);
+            let (start, end) = covered_run(&entries, i, &supernet);
+            let covered: AddressCount = entries[start..=end].iter().map(|entry| entry.subnet.address_total()).sum();
+            let added = supernet.address_total().saturating_sub(covered);
+
+            if best.as_ref().is_none_or(|(best_added, ..)| added < *best_added) {
+                best = Some((added, start, end, supernet));
+            }
+        }
+
+        let Some((added, start, end, subnet)) = best else { break };
+        let extra_addresses = entries[start..=end]
+            .iter()
+            .fold(added, |total, entry| total + entry.extra_addresses);
+        entries.splice(start..=end, [Summary { subnet, extra_addresses }]);
+    }
+
+    Ok(entries)
+}
+
+fn covered_run<A: IpFamily>(entries: &[Summary<A>], i: usize, supernet: &Subnet<A>) -> (usize, usize) {
+    let mut start = i;
+    while start > 0 && supernet.contains_subnet(&entries[start - 1].subnet) {
+        start -= 1;
+    }
+    let mut end = i + 1;
+    while end + 1 < entries.len() && supernet.contains_subnet(&entries[end + 1].subnet) {
+        end += 1;
+    }
+    (start, end)
+}
+
+pub fn reduction_ratio(input_count: usize, output_count: usize) -> f64 {
+    if input_count == 0 {
+        return 0.0;
+    }
+    (1.0 - output_count as f64 / input_count as f64) * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    fn prefixes(input: &str) -> Vec<Subnet> {
+        parse_prefix_list(input).unwrap()
+    }
+
+    fn cidrs(subnets: &[Subnet]) -> Vec<String> {
+        subnets.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_aggregate_merges_siblings_and_covered() {
+        let input = prefixes("10.0.0.0/24, 10.0.1.0/24, 10.0.2.0/24, 10.0.3.0/24, 10.0.2.128/25, 192.168.0.0/24");
+        assert_eq!(cidrs(&aggregate(&input)), vec!["10.0.0.0/22", "192.168.0.0/24"]);
+    }
+
+    #[test]
+    fn test_aggregate_keeps_unaligned_neighbours_apart() {
+        let input = prefixes("10.0.1.0/24\n10.0.2.0/24");
+        assert_eq!(cidrs(&aggregate(&input)), vec!["10.0.1.0/24", "10.0.2.0/24"]);
+    }
+
+    #[test]
+    fn test_aggregate_many_customer_prefixes() {
+        let input: Vec<Subnet> = (0..400u32)
+            .map(|i| Subnet::new(Ipv4Addr::from(0x0A00_0000 + (i << 8)), 24).unwrap())
+            .collect();
+        assert_eq!(cidrs(&aggregate(&input)), vec!["10.0.0.0/16", "10.1.0.0/17", "10.1.128.0/20"]);
+        assert!((reduction_ratio(400, 3) - 99.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_summarize_lossy() {
+        let input = prefixes("10.0.0.0/24, 10.0.2.0/24, 10.0.8.0/24");
+        let summaries = summarize(&input, 2).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].subnet.to_string(), "10.0.0.0/22");
+        assert_eq!(summaries[0].extra_addresses, 512.into());
+        assert_eq!(summaries[1].subnet.to_string(), "10.0.8.0/24");
+        assert_eq!(summaries[1].extra_addresses, AddressCount::ZERO);
+
+        let single = summarize(&input, 1).unwrap();
+        assert_eq!(single[0].subnet.to_string(), "10.0.0.0/20");
+        assert_eq!(single[0].extra_addresses, (4096 - 768).into());
+    }
+
+    #[test]
+    fn test_summarize_without_loss_when_allowed() {
+        let input = prefixes("10.0.0.0/25, 10.0.0.128/25");
+        let summaries = summarize(&input, 4).unwrap();
+        assert_eq!(summaries, vec![Summary { subnet: input[0].common_supernet(&input[1]), extra_addresses: AddressCount::ZERO }]);
+        assert!(summarize(&input, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_prefix_list_accepts_addresses() {
+        let input = prefixes("10.0.0.1, 10.0.0.0/30");
+        assert_eq!(cidrs(&input), vec!["10.0.0.1/32", "10.0.0.0/30"]);
+    }
+
+    #[test]
+    fn test_supernet_of_addresses() {
+        let input = prefixes("192.168.1.10, 192.168.1.20, 192.168.1.200");
+        let result = supernet(&input).unwrap();
+        assert_eq!(result.to_string(), "192.168.1.0/24");
+        assert_eq!(supernet_extra_addresses(&result, &input), 253.into());
+    }
+
+    #[test]
+    fn test_supernet_of_prefixes() {
+        let input = prefixes("10.0.0.0/24, 10.0.3.0/24");
+        let result = supernet(&input).unwrap();
+        assert_eq!(result.to_string(), "10.0.0.0/22");
+        assert_eq!(supernet_extra_addresses(&result, &input), 512.into());
+
+        let nested = prefixes("10.0.0.0/8, 10.1.2.0/24");
+        assert_eq!(supernet(&nested).unwrap().to_string(), "10.0.0.0/8");
+
+        let disjoint = prefixes("10.0.0.1, 192.168.0.1");
+        assert_eq!(supernet(&disjoint).unwrap().to_string(), "0.0.0.0/0");
+
+        let single = prefixes("10.0.0.1");
+        assert_eq!(supernet(&single).unwrap().to_string(), "10.0.0.1/32");
+        assert!(supernet::<Ipv4Addr>(&[]).is_none());
+    }
+
+    #[test]
+    fn test_ipv6_aggregate() {
+        let input: Vec<Subnet<Ipv6Addr>> = parse_prefix_list("2001:db8::/33, 2001:db8:8000::/33").unwrap();
+        let out: Vec<String> = aggregate(&input).iter().map(|s| s.to_string()).collect();
+        assert_eq!(out, vec!["2001:db8::/32"]);
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{AddressCount, IpCalculatorError, IpFamily, Subnet};
+
+#[derive(Debug)]
+pub enum ArithmeticOperation {
+    Add,
+    Subtract,
+    NthHost,
+    Distance,
+    NextNetwork,
+    PreviousNetwork,
+}
+
+impl FromStr for ArithmeticOperation {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "add" => Ok(ArithmeticOperation::Add),
+            "sub" | "subtract" => Ok(ArithmeticOperation::Subtract),
+            "nth" | "nth-host" => Ok(ArithmeticOperation::NthHost),
+            "distance" | "dist" => Ok(ArithmeticOperation::Distance),
+            "next" => Ok(ArithmeticOperation::NextNetwork),
+            "prev" | "previous" => Ok(ArithmeticOperation::PreviousNetwork),
+            _ => Err(IpCalculatorError::ArgumentsError(format!(
+                "Unknown arithmetic operation '{}'. Expected add, sub, nth, distance, next or prev",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ArithmeticOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ArithmeticOperation::Add => "add",
+            ArithmeticOperation::Subtract => "sub",
+            ArithmeticOperation::NthHost => "nth",
+            ArithmeticOperation::Distance => "distance",
+            ArithmeticOperation::NextNetwork => "next",
+            ArithmeticOperation::PreviousNetwork => "prev",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn add_offset<A: IpFamily>(ip: A, offset: i128) -> Result<A, IpCalculatorError> {
+    let bits = ip.to_bits();
+    let result = if offset >= 0 {
+        bits.checked_add(offset.unsigned_abs())
+    } else {
+        bits.checked_sub(offset.unsigned_abs())
+    };
+
+    match result.filter(|bits| *bits <= A::max_bits()) {
+        Some(bits) => Ok(A::from_bits(bits)),
+        None => Err(IpCalculatorError::AddressOverflow(format!(
+            "{} {} {} falls outside the address space",
+            ip,
+            if offset >= 0 { "+" } else { "-" },
+            offset.unsigned_abs()
+        ))),
+    }
+}
+
+pub fn distance<A: IpFamily>(from: A, to: A) -> Result<i128, IpCalculatorError> {
+    let (from_bits, to_bits) = (from.to_bits(), to.to_bits());
+    let overflow = || IpCalculatorError::AddressOverflow(format!(
+        "The distance from {} to {} does not fit in a signed 128-bit integer",
+        from, to
+    ));
+
+    if to_bits >= from_bits {
+        i128::try_from(to_bits - from_bits).map_err(|_| overflow())
+    } else {
+        i128::try_from(from_bits - to_bits).map(|d| -d).map_err(|_| overflow())
+    }
+}
+
+pub fn nth_host<A: IpFamily>(subnet: &Subnet<A>, index: i128) -> Result<A, IpCalculatorError> {
+    let reserved = if A::HAS_BROADCAST && subnet.prefix < A::BITS - 1 { 2 } else { 0 };
+    let hosts = subnet.address_total().saturating_sub(AddressCount::from(reserved));
+    let first = subnet.first_usable.unwrap_or(subnet.network);
+
+    let position = match hosts {
+        AddressCount::Exact(count) if index >= 0 => Some(index.unsigned_abs()).filter(|position| *position < count),
+        AddressCount::Exact(count) => count.checked_sub(index.unsigned_abs()),
+        AddressCount::AllIpv6 if index >= 0 => Some(index.unsigned_abs()),
+        AddressCount::AllIpv6 => Some(u128::MAX - (index.unsigned_abs() - 1)),
+    };
+
+    match position {
+        Some(position) => Ok(A::from_bits(first.to_bits() + position)),
+        None => Err(IpCalculatorError::RangeError(format!(
+            "Host index {} is out of range for {} ({} hosts)",
+            index, subnet, hosts
+        ))),
+    }
+}
+
+pub fn step_network<A: IpFamily>(subnet: &Subnet<A>, steps: i128) -> Result<Subnet<A>, IpCalculatorError> {
+    let size = A::host_bits(subnet.prefix).checked_add(1);
+    let offset = size.and_then(|size| steps.unsigned_abs().checked_mul(size));
+    let network = subnet.network.to_bits();
+
+    let result = match offset {
+        Some(offset) if steps >= 0 => network.checked_add(offset),
+        Some(offset) => network.checked_sub(offset),
+        None => None,
+    };
+
+    match result.filter(|bits| *bits <= A::max_bits()) {
+        Some(bits) => Ok(Subnet::from_bits(bits, subnet.prefix)),
+        None => Err(IpCalculatorError::AddressOverflow(format!(
+            "There is no {} /{} network {} {}",
+            if steps >= 0 { "next" } else { "previous" },
+            subnet.prefix,
+            if steps >= 0 { "after" } else { "before" },
+            subnet
+        ))),
+    }
+}
+
+pub fn next_network<A: IpFamily>(subnet: &Subnet<A>) -> Result<Subnet<A>, IpCalculatorError> {
+    step_network(subnet, 1)
+}
+
+pub fn previous_network<A: IpFamily>(subnet: &Subnet<A>) -> Result<Subnet<A>, IpCalculatorError> {
+    step_network(subnet, -1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_add_offset() {
+        let ip = Ipv4Addr::new(10, 0, 0, 250);
+        assert_eq!(add_offset(ip, 10).unwrap(), Ipv4Addr::new(10, 0, 1, 4));
+        assert_eq!(add_offset(ip, -251).unwrap(), Ipv4Addr::new(9, 255, 255, 255));
+        assert_eq!(add_offset(ip, 0).unwrap(), ip);
+    }
+
+    #[test]
+    fn test_add_offset_overflow() {
+        assert!(matches!(add_offset(Ipv4Addr::BROADCAST, 1), Err(IpCalculatorError::AddressOverflow(_))));
+        assert!(matches!(add_offset(Ipv4Addr::UNSPECIFIED, -1), Err(IpCalculatorError::AddressOverflow(_))));
+        assert!(add_offset(Ipv4Addr::new(1, 0, 0, 0), i128::MAX).is_err());
+        assert!(add_offset(Ipv6Addr::from(u128::MAX), 1).is_err());
+        assert_eq!(add_offset(Ipv6Addr::UNSPECIFIED, 1).unwrap(), Ipv6Addr::LOCALHOST);
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = Ipv4Addr::new(10, 0, 0, 1);
+        let b = Ipv4Addr::new(10, 0, 1, 1);
+        assert_eq!(distance(a, b).unwrap(), 256);
+        assert_eq!(distance(b, a).unwrap(), -256);
+        assert_eq!(distance(Ipv4Addr::UNSPECIFIED, Ipv4Addr::BROADCAST).unwrap(), u32::MAX as i128);
+        assert!(distance(Ipv6Addr::UNSPECIFIED, Ipv6Addr::from(u128::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_nth_host() {
+        let net = "192.168.1.0/24".parse::<Subnet>().unwrap();
+        assert_eq!(nth_host(&net, 0).unwrap(), Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(nth_host(&net, 9).unwrap(), Ipv4Addr::new(192, 168, 1, 10));
+        assert_eq!(nth_host(&net, -1).unwrap(), Ipv4Addr::new(192, 168, 1, 254));
+        assert_eq!(nth_host(&net, -254).unwrap(), Ipv4Addr::new(192, 168, 1, 1));
+        assert!(matches!(nth_host(&net, 254), Err(IpCalculatorError::RangeError(_))));
+        assert!(nth_host(&net, -255).is_err());
+
+        let link = "10.0.0.0/31".parse::<Subnet>().unwrap();
+        assert_eq!(nth_host(&link, -1).unwrap(), Ipv4Addr::new(10, 0, 0, 1));
+
+        let everything = "::/0".parse::<Subnet<Ipv6Addr>>().unwrap();
+        assert_eq!(nth_host(&everything, -1).unwrap(), Ipv6Addr::from(u128::MAX));
+        assert_eq!(nth_host(&everything, 0).unwrap(), Ipv6Addr::UNSPECIFIED);
+        assert_eq!(nth_host(&everything, i128::MIN).unwrap(), Ipv6Addr::from(1u128 << 127));
+    }
+
+    #[test]
+    fn test_step_network() {
+        let net = "10.0.0.64/26".parse::<Subnet>().unwrap();
+        assert_eq!(next_network(&net).unwrap().to_string(), "10.0.0.128/26");
+        assert_eq!(previous_network(&net).unwrap().to_string(), "10.0.0.0/26");
+        assert_eq!(step_network(&net, 4).unwrap().to_string(), "10.0.1.64/26");
+        assert!(matches!(previous_network(&"0.0.0.0/8".parse::<Subnet>().unwrap()), Err(IpCalculatorError::AddressOverflow(_))));
+        assert!(next_network(&"255.0.0.0/8".parse::<Subnet>().unwrap()).is_err());
+        assert!(next_network(&"0.0.0.0/0".parse::<Subnet>().unwrap()).is_err());
+
+        let v6: Subnet<Ipv6Addr> = "2001:db8::/64".parse().unwrap();
+        assert_eq!(next_network(&v6).unwrap().to_string(), "2001:db8:0:1::/64");
+    }
+}
@@ -1,7 +1,24 @@
 use std::fmt;
-use std::net::Ipv4Addr;
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
+use crate::libs::aggregate::{
+    aggregate, parse_prefix_list, summarize, supernet, supernet_extra_addresses, Summary,
+};
+use crate::libs::arithmetic::{add_offset, distance, next_network, nth_host, previous_network, ArithmeticOperation};
+use crate::libs::export::OutputFile;
+use crate::libs::ip_range::IpRange;
+use crate::libs::ip_set::{address_exclude, list_items, IpSet};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::notation::{self, parse_ipv4_any};
+use crate::libs::reverse_dns::{classless_delegation, classless_zone, ptr_owner, reverse_zones, CnameRecord};
+use crate::libs::route_table::{parse_routes, Route, RouteTable};
+use crate::libs::special_purpose::{self, Classification};
+use crate::libs::subnet_iter::{HostIter, SubnetIter};
+use crate::libs::vlsm::{parse_requirements, plan_vlsm, Allocation};
+use crate::libs::zone_file::{HostnameTemplate, ZoneFile};
+
 pub enum IpCalculatorError {
     InvalidPrefix(String),
     InvalidMask(String),
@@ -14,6 +31,9 @@ pub enum IpCalculatorError {
     RangeError(String),
     ConversionError(String),
     ArgumentsError(String),
+    InputError(String),
+    NonContiguousMask(MaskDiagnostic),
+    AddressOverflow(String),
 }
 
 impl std::error::Error for IpCalculatorError {}
@@ -32,6 +52,9 @@ impl std::fmt::Debug for IpCalculatorError {
             Self::RangeError(msg) => write!(f, "RangeError({})", msg),
             Self::ConversionError(msg) => write!(f, "ConversionError({})", msg),
             Self::ArgumentsError(msg) => write!(f, "ArgumentsError({})", msg),
+            Self::InputError(msg) => write!(f, "InputError({})", msg),
+            Self::NonContiguousMask(diagnostic) => write!(f, "NonContiguousMask({:?})", diagnostic),
+            Self::AddressOverflow(msg) => write!(f, "AddressOverflow({})", msg),
         }
     }
 }
@@ -50,12 +73,131 @@ impl fmt::Display for IpCalculatorError {
             Self::RangeError(msg) => write!(f, "Range error: {}", msg),
             Self::ConversionError(msg) => write!(f, "Conversion error: {}", msg),
             Self::ArgumentsError(msg) => write!(f, "Arguments error: {}", msg),
+            Self::InputError(msg) => write!(f, "Input error: {}", msg),
+            Self::NonContiguousMask(diagnostic) => write!(f, "Invalid mask: {}", diagnostic),
+            Self::AddressOverflow(msg) => write!(f, "Address overflow: {}", msg),
+        }
+    }
+}
+
+impl IpCalculatorError {
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::InvalidPrefix(_) => "InvalidPrefix",
+            Self::InvalidMask(_) => "InvalidMask",
+            Self::InvalidIP(_) => "InvalidIP",
+            Self::InvalidCIDR(_) => "InvalidCIDR",
+            Self::InvalidRange(_) => "InvalidRange",
+            Self::SubnetError(_) => "SubnetError",
+            Self::IpError(_) => "IpError",
+            Self::MaskError(_) => "MaskError",
+            Self::RangeError(_) => "RangeError",
+            Self::ConversionError(_) => "ConversionError",
+            Self::ArgumentsError(_) => "ArgumentsError",
+            Self::InputError(_) => "InputError",
+            Self::NonContiguousMask(_) => "NonContiguousMask",
+            Self::AddressOverflow(_) => "AddressOverflow",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ArgumentsError(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl ToJson for IpCalculatorError {
+    fn to_json(&self) -> Json {
+        let mut error = Json::object([
+            ("type", self.variant_name().into()),
+            ("message", self.to_string().into()),
+        ]);
+        if let Self::NonContiguousMask(diagnostic) = self {
+            error.push("diagnostic", diagnostic.to_json());
+        }
+        Json::object([("error", error)])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskDiagnostic {
+    pub mask: String,
+    pub bits: u128,
+    pub width: u8,
+    pub position: u8,
+}
+
+impl MaskDiagnostic {
+    pub fn new<A: IpFamily>(mask: A) -> Option<Self> {
+        let bits = mask.to_bits();
+        let leading_ones = (bits << (128 - A::BITS as u32)).leading_ones() as u8;
+        let host_bits = A::host_bits(leading_ones);
+        if leading_ones >= A::BITS || bits & host_bits == 0 {
+            return None;
         }
+
+        let position = (bits & host_bits).leading_zeros() - (128 - A::BITS as u32);
+        Some(MaskDiagnostic {
+            mask: mask.to_string(),
+            bits,
+            width: A::BITS,
+            position: position as u8,
+        })
+    }
+
+    pub fn binary(&self) -> String {
+        notation::binary_string(self.bits, self.width)
+    }
+
+    pub fn marker(&self) -> String {
+        let offset = self.position as usize + self.position as usize / notation::group_size(self.width) as usize;
+        format!("{}^", " ".repeat(offset))
+    }
+
+    pub fn suggested_prefixes(&self) -> [u8; 2] {
+        let mut prefixes: Vec<(u32, u8)> = (0..=self.width)
+            .map(|prefix| {
+                let host_bits = u128::MAX.checked_shr(128 - self.width as u32 + prefix as u32).unwrap_or(0);
+                let mask = (u128::MAX >> (128 - self.width as u32)) & !host_bits;
+                ((mask ^ self.bits).count_ones(), prefix)
+            })
+            .collect();
+        prefixes.sort_unstable();
+        let mut nearest = [prefixes[0].1, prefixes[1].1];
+        nearest.sort_unstable();
+        nearest
+    }
+}
+
+impl ToJson for MaskDiagnostic {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("mask", self.mask.as_str().into()),
+            ("binary", self.binary().into()),
+            ("bit", (self.position + 1).into()),
+            ("width", self.width.into()),
+            ("suggested_prefixes", self.suggested_prefixes().to_vec().into()),
+        ])
+    }
+}
+
+impl fmt::Display for MaskDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is not contiguous: bit {} of {} is set after a host bit",
+            self.mask,
+            self.position + 1,
+            self.width
+        )
     }
 }
 pub enum MaskOrCidr {
     Mask(Ipv4Addr),
     Cidr(u8),
+    Wildcard(Ipv4Addr),
 }
 
 impl MaskOrCidr {
@@ -71,11 +213,59 @@ impl MaskOrCidr {
             _ => unreachable!(),
         }
     }
+    pub fn expect_wildcard(self) -> Ipv4Addr {
+        match self {
+            MaskOrCidr::Wildcard(w) => w,
+            _ => unreachable!(),
+        }
+    }
 }
 
+#[derive(Debug)]
 pub enum InputType {
     Cidr,
     Mask,
+    Wildcard,
+}
+
+#[derive(Debug)]
+pub enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+    Complement,
+    Subset,
+}
+
+impl FromStr for SetOperation {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "union" => Ok(SetOperation::Union),
+            "intersection" | "intersect" => Ok(SetOperation::Intersection),
+            "difference" | "diff" => Ok(SetOperation::Difference),
+            "complement" => Ok(SetOperation::Complement),
+            "subset" => Ok(SetOperation::Subset),
+            _ => Err(IpCalculatorError::ArgumentsError(format!(
+                "Unknown set operation '{}'. Expected union, intersection, difference, complement or subset",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SetOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SetOperation::Union => "union",
+            SetOperation::Intersection => "intersection",
+            SetOperation::Difference => "difference",
+            SetOperation::Complement => "complement",
+            SetOperation::Subset => "subset",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 pub enum Command {
@@ -84,33 +274,101 @@ pub enum Command {
         prefix: u8,
         filter: Option<usize>,
         page: Option<usize>,
-        output_file: Option<String>,
+        output_file: Option<OutputFile>,
     },
     GetSubnet {
         cidr: String,
         prefix: u8,
-        index: u32,
+        index: u128,
+        output_file: Option<OutputFile>,
     },
     SameSubnet {
-        ip1: Ipv4Addr,
-        ip2: Ipv4Addr,
-        mask1: Ipv4Addr,
-        mask2: Option<Ipv4Addr>,
+        ip1: IpAddr,
+        ip2: IpAddr,
+        mask1: IpAddr,
+        mask2: Option<IpAddr>,
     },
     CheckIP {
         ip: String,
     },
     CheckMask {
         mask: String,
+        wildcard: bool,
     },
     FindRange {
         cidr: String,
         range_size: usize,
-        exclusions: Vec<Ipv4Addr>,
+        exclusions: Vec<IpAddr>,
     },
     Display {
         cidr: String,
+        verbose: bool,
+        output_file: Option<OutputFile>,
+    },
+    RangeToCidr {
+        range: String,
+        output_file: Option<OutputFile>,
+    },
+    Set {
+        operation: SetOperation,
+        left: String,
+        right: Option<String>,
+    },
+    Aggregate {
+        prefixes: String,
+        max_entries: Option<usize>,
+    },
+    Supernet {
+        prefixes: String,
+    },
+    Exclude {
+        cidr: String,
+        excluded: String,
+        output_file: Option<OutputFile>,
+    },
+    Vlsm {
+        cidr: String,
+        requirements: String,
     },
+    Lookup {
+        routes: String,
+        ips: Vec<IpAddr>,
+    },
+    Classify {
+        inputs: Vec<String>,
+    },
+    Convert {
+        values: Vec<String>,
+    },
+    Arithmetic {
+        operation: ArithmeticOperation,
+        operands: Vec<String>,
+    },
+    ReverseDns {
+        cidr: String,
+        filter: Option<usize>,
+    },
+    PtrZone {
+        cidr: String,
+        template: String,
+        names: Option<String>,
+        nameservers: Vec<String>,
+        output_file: Option<OutputFile>,
+    },
+}
+
+impl Command {
+    pub fn output_file(&self) -> Option<&OutputFile> {
+        match self {
+            Command::Subnets { output_file, .. }
+            | Command::GetSubnet { output_file, .. }
+            | Command::Display { output_file, .. }
+            | Command::RangeToCidr { output_file, .. }
+            | Command::Exclude { output_file, .. }
+            | Command::PtrZone { output_file, .. } => output_file.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 pub struct CommandHelp {
@@ -130,12 +388,20 @@ impl CommandHelp {
                 aliases: &[],
                 short_desc: "Display subnet information",
                 long_desc: "Display detailed information about a subnet, including network address, \
-                           broadcast address, mask, number of available hosts, and usable IP range.",
-                usage: "./ip_calculator <ip>/<cidr> or ./ip_calculator <ip> <mask or cidr>",
+                           broadcast address, mask, wildcard mask, number of available hosts, and usable IP \
+                           range. IPv6 prefixes show the first and last address and the address count. \
+                           Wildcard masks are detected automatically; add --wildcard to read an ambiguous \
+                           mask such as 0.0.0.0 as a wildcard mask. With --verbose the network, mask and \
+                           broadcast are also shown in binary with the network and host bits separated. \
+                           Use -o to also write the details to a file.",
+                usage: "./ip_calculator <ip>/<cidr> or ./ip_calculator <ip> <mask, wildcard or cidr> [--wildcard] [-v|--verbose] [-o <file>]",
                 examples: &[
                     "./ip_calculator 192.168.1.0/24",
                     "./ip_calculator 192.168.1.0 255.255.255.0",
+                    "./ip_calculator 192.168.1.0 0.0.0.255",
+                    "./ip_calculator 192.168.1.0/20 --verbose",
                     "./ip_calculator 10.0.0.0 24",
+                    "./ip_calculator 2001:db8::/32",
                 ],
             },
             CommandHelp {
@@ -145,13 +411,16 @@ impl CommandHelp {
                 long_desc: "Divide a network into smaller subnets. The command ensures no subnet overlapping \
                            and provides detailed information for each subnet. Use the -f option to limit the \
                            number of displayed subnets by default 4 if is possible. Use -o to write all \
-                           matching subnets to a file.",
-                usage: "./ip_calculator (-s|--subnets) <CIDR> <new_prefix> [-f <number>] [-p <page (1-indexed)>] [-o <file>]",
+                           matching subnets to a file; a .csv or .tsv extension writes one row per subnet \
+                           and --columns picks and orders the columns (index, network, prefix, mask, \
+                           wildcard, broadcast, first, last, hosts).",
+                usage: "./ip_calculator (-s|--subnets) <CIDR> <new_prefix> [-f <number>] [-p <page (1-indexed)>] [-o <file> [--columns <list>]]",
                 examples: &[
                     "./ip_calculator -s 192.168.1.0/24 26",
                     "./ip_calculator --subnets 10.0.0.0/8 16 -f 5",
                     "./ip_calculator -s 10.0.0.0/15 30 -p 10",
-                    "./ip_calculator -s 10.0.0.0/16 24 -o output.txt"
+                    "./ip_calculator -s 10.0.0.0/16 24 -o output.txt",
+                    "./ip_calculator -s 10.0.0.0/16 24 -o subnets.csv --columns network,prefix,hosts",
                 ],
             },
             CommandHelp {
@@ -159,8 +428,9 @@ impl CommandHelp {
                 aliases: &["--get-subnet"],
                 short_desc: "Get a specific subnet by index",
                 long_desc: "Retrieve information about a specific subnet by its index after dividing the \
-                           network. The index starts at 0 and must be within the range of possible subnets.",
-                usage: "./ip_calculator --get-subnet <CIDR> <new_prefix> <index>",
+                           network. The index starts at 0 and must be within the range of possible subnets. \
+                           Use -o to also write the subnet to a text, .csv or .tsv file.",
+                usage: "./ip_calculator --get-subnet <CIDR> <new_prefix> <index> [-o <file> [--columns <list>]]",
                 examples: &[
                     "./ip_calculator --get-subnet 192.168.1.0/24 26 2",
                     "./ip_calculator --get-subnet 10.0.0.0/8 16 5",
@@ -171,10 +441,13 @@ impl CommandHelp {
                 aliases: &["-same", "--same-subnet"],
                 short_desc: "Check if IPs are in the same subnet",
                 long_desc: "Verify if two IP addresses belong to the same subnet. You can specify different \
-                           masks for each IP. If only one mask is provided, it will be used for both IPs.",
-                usage: "./ip_calculator (-same|--same-subnet) <IP1> <IP2> <mask1> [mask2]",
+                           masks for each IP. If only one mask is provided, it will be used for both IPs. \
+                           Exits with 0 when they share a subnet and 1 when they do not; add -q to only \
+                           set the exit status.",
+                usage: "./ip_calculator (-same|--same-subnet) <IP1> <IP2> <mask1> [mask2] [-q]",
                 examples: &[
                     "./ip_calculator -same 192.168.1.10 192.168.1.20 255.255.255.0",
+                    "if ./ip_calculator -same 10.0.0.1 10.0.1.1 24 -q; then echo same; fi",
                     "./ip_calculator --same-subnet 10.0.0.1 10.0.0.2 255.0.0.0 255.255.0.0",
                 ],
             },
@@ -183,8 +456,8 @@ impl CommandHelp {
                 aliases: &["-cip", "--check-ip"],
                 short_desc: "Validate IP address format",
                 long_desc: "Check if an IP address is valid according to IPv4 format rules. Each octet \
-                           must be between 0 and 255.",
-                usage: "./ip_calculator (-cip|--check-ip) <IP>",
+                           must be between 0 and 255. Exits with 0 for a valid address and 1 otherwise.",
+                usage: "./ip_calculator (-cip|--check-ip) <IP> [-q]",
                 examples: &[
                     "./ip_calculator -cip 192.168.1.1",
                     "./ip_calculator --check-ip 10.0.0.1",
@@ -194,12 +467,19 @@ impl CommandHelp {
                 name: "check-mask",
                 aliases: &["-cmask", "--check-mask"],
                 short_desc: "Validate subnet mask",
-                long_desc: "Verify if a subnet mask is valid. Can accept both traditional mask format \
-                           (e.g., 255.255.255.0) and CIDR notation (e.g., 24).",
-                usage: "./ip_calculator (-cmask|--check-mask) <mask|cidr>",
+                long_desc: "Verify if a subnet mask is valid. Can accept traditional mask format \
+                           (e.g., 255.255.255.0), wildcard mask format (e.g., 0.0.0.255) and CIDR notation \
+                           (e.g., 24), and reports the mask in all three notations. Use --wildcard to read \
+                           an ambiguous mask such as 0.0.0.0 as a wildcard mask. A non-contiguous mask is \
+                           shown in binary with the first offending bit marked, along with the two nearest \
+                           valid masks. Exits with 0 for a valid mask and 1 otherwise.",
+                usage: "./ip_calculator (-cmask|--check-mask) <mask|wildcard|CIDR> [--wildcard] [-q]",
                 examples: &[
                     "./ip_calculator -cmask 255.255.255.0",
                     "./ip_calculator --check-mask 24",
+                    "./ip_calculator --check-mask 0.0.0.255",
+                    "./ip_calculator --check-mask 0.0.0.0 --wildcard",
+                    "./ip_calculator --check-mask 255.255.0.255",
                 ],
             },
             CommandHelp {
@@ -215,6 +495,260 @@ impl CommandHelp {
                     "./ip_calculator --find-range 10.0.0.0/24 5 10.0.0.1 10.0.0.2",
                 ],
             },
+            CommandHelp {
+                name: "range-to-cidr",
+                aliases: &["-rc", "--range-to-cidr"],
+                short_desc: "Convert an IP range to CIDRs",
+                long_desc: "Decompose an arbitrary start-end range of addresses into the minimal list of \
+                           CIDR prefixes that covers exactly that range. Use -o to write the prefixes to a \
+                           text, .csv or .tsv file.",
+                usage: "./ip_calculator (-rc|--range-to-cidr) <start> - <end> [-o <file> [--columns <list>]]",
+                examples: &[
+                    "./ip_calculator -rc 10.0.3.17 - 10.0.9.200",
+                    "./ip_calculator -rc 10.0.3.17 - 10.0.9.200 -o range.tsv",
+                    "./ip_calculator --range-to-cidr 2001:db8::1-2001:db8::ffff",
+                ],
+            },
+            CommandHelp {
+                name: "set",
+                aliases: &["--set"],
+                short_desc: "Set algebra over lists of prefixes",
+                long_desc: "Combine two lists of prefixes, ranges and single addresses with union, \
+                           intersection or difference, take the complement of one list, or test whether \
+                           the first list is a subset of the second. Each list is either a comma-separated \
+                           argument or a file with one entry per line. The result is printed as a minimal \
+                           CIDR list. The subset test exits with 1 when the first list is not a subset.",
+                usage: "./ip_calculator --set <union|intersection|difference|subset> <list|file> <list|file>\n\
+                        ./ip_calculator --set complement <list|file>",
+                examples: &[
+                    "./ip_calculator --set union 10.0.0.0/24,10.0.1.0/24 10.0.2.0/23",
+                    "./ip_calculator --set difference 10.0.0.0/16 deny.txt",
+                    "./ip_calculator --set subset 10.0.5.0/24 10.0.0.0/16",
+                ],
+            },
+            CommandHelp {
+                name: "aggregate",
+                aliases: &["-agg", "--aggregate"],
+                short_desc: "Summarize a list of prefixes",
+                long_desc: "Collapse many prefixes into the smallest equivalent set of supernets by merging \
+                           adjacent aligned siblings and dropping covered prefixes. Prefixes are given as \
+                           arguments, comma-separated lists or files. With --max the result is summarized \
+                           lossily down to at most that many entries, and the extra addresses each summary \
+                           includes are reported.",
+                usage: "./ip_calculator (-agg|--aggregate) <prefix|list|file>... [--max <entries>]",
+                examples: &[
+                    "./ip_calculator -agg 10.0.0.0/24 10.0.1.0/24 10.0.2.0/23",
+                    "./ip_calculator --aggregate customers.txt",
+                    "./ip_calculator --aggregate customers.txt --max 4",
+                ],
+            },
+            CommandHelp {
+                name: "supernet",
+                aliases: &["-super", "--supernet"],
+                short_desc: "Find the smallest common supernet",
+                long_desc: "Find the longest prefix that contains every given address or prefix, display it \
+                           and report how many addresses it covers beyond the inputs.",
+                usage: "./ip_calculator (-super|--supernet) <IP|CIDR|list|file>...",
+                examples: &[
+                    "./ip_calculator -super 192.168.1.10 192.168.1.20 192.168.1.200",
+                    "./ip_calculator --supernet 10.0.0.0/24 10.0.3.0/24",
+                ],
+            },
+            CommandHelp {
+                name: "exclude",
+                aliases: &["-ex", "--exclude"],
+                short_desc: "Remove prefixes or ranges from a network",
+                long_desc: "Carve prefixes, ranges or single addresses out of a base network and print the \
+                           minimal sorted list of CIDRs that remain, each with its host count. Exclusions \
+                           are given as arguments, comma-separated lists or files. Use -o to write the \
+                           remaining subnets to a text, .csv or .tsv file.",
+                usage: "./ip_calculator (-ex|--exclude) <CIDR> <prefix|range|list|file>... [-o <file> [--columns <list>]]",
+                examples: &[
+                    "./ip_calculator -ex 10.0.0.0/16 10.0.1.0/24 10.0.4.0/24",
+                    "./ip_calculator --exclude 192.168.0.0/24 \"192.168.0.10 - 192.168.0.20\"",
+                    "./ip_calculator --exclude 10.0.0.0/8 reserved.txt -o free.csv",
+                ],
+            },
+            CommandHelp {
+                name: "vlsm",
+                aliases: &["-vlsm", "--vlsm"],
+                short_desc: "Plan variable-size subnets from host requirements",
+                long_desc: "Allocate one subnet per named requirement from a parent network. Each requirement \
+                           gets the smallest block with enough usable hosts, and blocks are placed \
+                           largest-first so every subnet is aligned. The plan is printed as a table with the \
+                           usable range and the number of wasted host addresses of each subnet, followed by \
+                           the space left in the parent. Requirements are given as arguments, \
+                           comma-separated lists or a file.",
+                usage: "./ip_calculator (-vlsm|--vlsm) <CIDR> <name=hosts|list|file>...",
+                examples: &[
+                    "./ip_calculator --vlsm 192.168.10.0/24 users=100 printers=20 wan1=2 wan2=2",
+                    "./ip_calculator -vlsm 10.20.0.0/16 site-plan.txt",
+                ],
+            },
+            CommandHelp {
+                name: "lookup",
+                aliases: &["-lpm", "--lookup"],
+                short_desc: "Find the route each destination takes",
+                long_desc: "Load a route table file with one '<CIDR> <next-hop> [metric]' entry per line and \
+                           report the route chosen for each destination IP by longest-prefix match. When \
+                           several routes share the longest prefix, the lowest metric wins (missing metrics \
+                           count as 0). Lines starting with # are ignored.",
+                usage: "./ip_calculator (-lpm|--lookup) <route_file> <IP>...",
+                examples: &[
+                    "./ip_calculator --lookup routes.txt 10.1.2.3",
+                    "./ip_calculator -lpm routes.txt 8.8.8.8 2001:db8::1",
+                ],
+            },
+            CommandHelp {
+                name: "classify",
+                aliases: &["-cls", "--classify"],
+                short_desc: "Identify special-purpose address space",
+                long_desc: "Check addresses or CIDRs against the built-in IANA special-purpose registry \
+                           (RFC 6890) and report their category (private, shared, loopback, link-local, \
+                           multicast, documentation, benchmarking, reserved or global unicast) with the \
+                           forwardable and globally reachable flags. A CIDR spanning several categories is \
+                           reported as mixed.",
+                usage: "./ip_calculator (-cls|--classify) <IP|CIDR>...",
+                examples: &[
+                    "./ip_calculator --classify 100.64.12.1",
+                    "./ip_calculator -cls 10.0.0.0/8 198.51.100.0/24 fe80::1",
+                ],
+            },
+            CommandHelp {
+                name: "convert",
+                aliases: &["-cv", "--convert"],
+                short_desc: "Convert an IPv4 address or mask between notations",
+                long_desc: "Read an IPv4 address or mask written as a dotted quad, a 32-bit integer, hex \
+                           (0x0A000001) or binary (0b..., 32 bits, or dotted 8-bit groups) and print it in \
+                           all four notations. Masks are also reported as prefix lengths.",
+                usage: "./ip_calculator (-cv|--convert) <value>...",
+                examples: &[
+                    "./ip_calculator --convert 167772161",
+                    "./ip_calculator -cv 0xFFFFFF00",
+                    "./ip_calculator --convert 11000000.10101000.00000001.00000001",
+                ],
+            },
+            CommandHelp {
+                name: "ip-math",
+                aliases: &["-math", "--ip-math"],
+                short_desc: "Do arithmetic on addresses and networks",
+                long_desc: "Add or subtract an offset from an address, pick the Nth usable host of a subnet \
+                           (0 is the first host, negative values count back from the last host), measure the \
+                           distance between two addresses, or step to the next or previous aligned network \
+                           of a prefix. Results outside the address space are reported as an overflow \
+                           instead of wrapping around.",
+                usage: "./ip_calculator (-math|--ip-math) add|sub <IP> <offset>\n\
+                        ./ip_calculator (-math|--ip-math) nth <CIDR> <index>\n\
+                        ./ip_calculator (-math|--ip-math) distance <IP1> <IP2>\n\
+                        ./ip_calculator (-math|--ip-math) next|prev <CIDR>|<IP> <prefix>",
+                examples: &[
+                    "./ip_calculator --ip-math add 10.0.0.250 10",
+                    "./ip_calculator --ip-math nth 192.168.1.0/24 -1",
+                    "./ip_calculator -math distance 10.0.0.1 10.0.3.200",
+                    "./ip_calculator -math next 10.0.0.64/26",
+                ],
+            },
+            CommandHelp {
+                name: "reverse-dns",
+                aliases: &["-rdns", "--reverse-dns"],
+                short_desc: "Show the reverse DNS zones and PTR names of a CIDR",
+                long_desc: "Lists the in-addr.arpa or ip6.arpa zones that cover a CIDR and the PTR owner name of \
+                           each address. Prefixes between octet (or nibble) boundaries expand to every zone they \
+                           cover. IPv4 prefixes longer than /24 use RFC 2317 classless delegation: the delegated \
+                           zone name is shown along with the CNAME records the parent zone must publish. Use -f \
+                           to change how many PTR names are listed (default 256).",
+                usage: "./ip_calculator (-rdns|--reverse-dns) <CIDR> [-f <number>]",
+                examples: &[
+                    "./ip_calculator --reverse-dns 192.168.4.0/22",
+                    "./ip_calculator --reverse-dns 192.0.2.64/26",
+                    "./ip_calculator -rdns 2001:db8::/126",
+                ],
+            },
+            CommandHelp {
+                name: "ptr-zone",
+                aliases: &["-ptr", "--ptr-zone"],
+                short_desc: "Generate a BIND reverse zone file for a CIDR",
+                long_desc: "Writes a BIND-format reverse zone with an SOA placeholder, NS records and one PTR \
+                           record per usable host. Network and broadcast addresses are skipped, except in /31 \
+                           and /32 networks where every address is a host. Hostnames come from a template with \
+                           {octet1}..{octet4} (IPv4), {hextet1}..{hextet8} (IPv6) and {ip} placeholders, or from \
+                           {name} looked up in a CSV file of <IP>,<name> lines given with --names; hosts without \
+                           a name are left out. Without --names, prefixes of more than 65536 hosts are refused; with \
+                           --names only the listed addresses are visited. Name servers default to a placeholder unless --ns is given. \
+                           Prefixes longer than /24 produce the RFC 2317 delegated zone.",
+                usage: "./ip_calculator (-ptr|--ptr-zone) <CIDR> <template> [--names <csv>] [--ns <nameserver>]... [-o <file>]",
+                examples: &[
+                    "./ip_calculator --ptr-zone 192.0.2.0/24 host-{octet3}-{octet4}.example.net --ns ns1.example.net",
+                    "./ip_calculator --ptr-zone 192.0.2.64/26 {name} --names hosts.csv -o 64-26.2.0.192.zone",
+                ],
+            },
+            CommandHelp {
+                name: "batch",
+                aliases: &["-b", "--batch"],
+                short_desc: "Run one command per line from a file or stdin",
+                long_desc: "Reads lines from the given file, or from stdin when no file or '-' is given, and \
+                           runs each one in the same process. A line is a bare CIDR, an <IP> <mask> pair or a \
+                           full command line, optionally starting with the program name. Blank lines and lines \
+                           starting with # are skipped. Every result is labelled with its line number, failing \
+                           lines are reported without stopping the run and a summary of failures is printed at \
+                           the end. Global flags such as --json, -v and --wildcard apply to every line. Exits \
+                           with 1 if any line failed.",
+                usage: "./ip_calculator (-b|--batch) [file]",
+                examples: &[
+                    "./ip_calculator --batch inventory.txt",
+                    "cut -d, -f1 export.csv | ./ip_calculator --batch --json",
+                ],
+            },
+            CommandHelp {
+                name: "shell",
+                aliases: &["--shell"],
+                short_desc: "Open an interactive prompt",
+                long_desc: "Starts a prompt that accepts the same commands without the program name, either \
+                           as flags (-s 10.0.0.0/16 24) or as command names (subnets 10.0.0.0/16 24). $_ holds \
+                           the last result and 'let <name> = <value>' defines variables used as $name. 'help \
+                           [command]' shows help, 'history' lists previous lines and !! or !<n> runs one again. \
+                           On a terminal, left/right, Home/End and backspace edit the line and up/down walk the \
+                           history. History is kept in ip_calculator/history under the user's config directory. Errors \
+                           are printed and the prompt continues; 'exit', 'quit' or end of input leave the shell.",
+                usage: "./ip_calculator --shell",
+                examples: &[
+                    "./ip_calculator --shell",
+                    "ip> let core = 10.0.0.0/16",
+                    "ip> subnets $core 24 -f 4",
+                    "ip> display $_",
+                ],
+            },
+            CommandHelp {
+                name: "completions",
+                aliases: &["--completions"],
+                short_desc: "Print a shell completion script",
+                long_desc: "Prints a completion script for bash, zsh or fish generated from this help table. \
+                           Command names and aliases complete in the first position, options taken from each \
+                           usage line complete after the command, fixed values such as the set operations or the \
+                           shell names complete as the first argument, and file paths complete after options \
+                           such as -o that take a file.",
+                usage: "./ip_calculator --completions <bash|zsh|fish>",
+                examples: &[
+                    "./ip_calculator --completions bash > /etc/bash_completion.d/ip_calculator",
+                    "./ip_calculator --completions zsh > ~/.zfunc/_ip_calculator",
+                    "./ip_calculator --completions fish > ~/.config/fish/completions/ip_calculator.fish",
+                ],
+            },
+            CommandHelp {
+                name: "generate-man",
+                aliases: &["--generate-man"],
+                short_desc: "Print the manual page generated from this help",
+                long_desc: "Prints a roff man page with NAME, SYNOPSIS, one section per command with its usage \
+                           and examples, the global options and the exit status. With markdown the same \
+                           reference is printed as Markdown, which is what the command section of the README \
+                           is generated from.",
+                usage: "./ip_calculator --generate-man [roff|markdown]",
+                examples: &[
+                    "./ip_calculator --generate-man > ip_calculator.1",
+                    "./ip_calculator --generate-man | man -l -",
+                    "./ip_calculator --generate-man markdown",
+                ],
+            },
             CommandHelp {
                 name: "help",
                 aliases: &["-h", "--help"],
@@ -236,630 +770,1539 @@ impl CommandHelp {
         })
     }
 
-    pub fn display_command_list() {
-        println!("\x1b[1;32mIP Calculator - Available Commands:\x1b[0m\n");
-        for cmd in Self::get_all() {
-            let aliases = if cmd.aliases.is_empty() {
-                String::new()
-            } else {
-                format!(" ({})", cmd.aliases.join(", "))
+    pub fn options(&self) -> Vec<CommandOption> {
+        let tokens: Vec<&'static str> = self.usage.split_whitespace().collect();
+        let mut options: Vec<CommandOption> = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let Some(flag) = token.strip_prefix('[').filter(|flag| flag.starts_with('-')) else {
+                continue;
+            };
+            let names: Vec<&'static str> = flag.trim_end_matches([']', '.']).split('|').collect();
+            let value = match tokens.get(i + 1) {
+                Some(next) if !flag.ends_with(']') && next.starts_with('<') => {
+                    Some(next.trim_matches(['<', '>', ']', '.']))
+                },
+                _ => None,
             };
-            println!("\x1b[1;34m{}{}\x1b[0m", cmd.name, aliases);
-            println!("    {}\n", cmd.short_desc);
+            if !options.iter().any(|option| option.names == names) {
+                options.push(CommandOption { names, value });
+            }
         }
-        println!("\nFor detailed help on a specific command, use: ./ip_calculator --help <command>");
+        options
     }
 
-    pub fn display_command_help(&self) {
-        println!("\x1b[1;32mHelp for command: {}\x1b[0m\n", self.name);
-        
-        if !self.aliases.is_empty() {
-            println!("\x1b[1;34mAliases:\x1b[0m {}\n", self.aliases.join(", "));
-        }
-        
-        println!("\x1b[1;34mDescription:\x1b[0m");
-        println!("{}\n", self.long_desc);
-        
-        println!("\x1b[1;34mUsage:\x1b[0m");
-        println!("{}\n", self.usage);
-        
-        println!("\x1b[1;34mExamples:\x1b[0m");
-        for example in self.examples {
-            println!("  {}", example);
+    pub fn values(&self) -> Vec<&'static str> {
+        let tokens: Vec<&'static str> = self.usage.split_whitespace().collect();
+        let mut values: Vec<&'static str> = Vec::new();
+        for line in tokens.windows(3).filter(|line| line[0] == "./ip_calculator") {
+            let (command, argument) = (line[1], line[2]);
+            if !command.starts_with(['(', '-']) || argument.ends_with("...") {
+                continue;
+            }
+            let inner = argument.trim_matches(['<', '>', '[', ']']);
+            if inner.len() != argument.len() && !inner.contains('|') {
+                continue;
+            }
+            if !inner.split('|').all(|value| !value.is_empty() && value.chars().all(|c| c.is_ascii_lowercase())) {
+                continue;
+            }
+            for value in inner.split('|') {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
         }
+        values
     }
 }
 
+pub struct GlobalOption {
+    pub names: &'static [&'static str],
+    pub description: &'static str,
+}
 
-#[derive(Debug)]
-pub struct Subnet {
-    pub network: Ipv4Addr,
-    pub mask: Ipv4Addr,
-    pub broadcast: Ipv4Addr,
-    pub first_usable: Option<Ipv4Addr>,
-    pub last_usable: Option<Ipv4Addr>,
-    pub prefix: u8,
-    pub num_hosts: u32,
+impl GlobalOption {
+    pub fn get_all() -> Vec<GlobalOption> {
+        vec![
+            GlobalOption {
+                names: &["--json"],
+                description: "Print a single JSON document instead of coloured text, including for errors and help. Not available with --completions or --generate-man.",
+            },
+            GlobalOption {
+                names: &["-q", "--quiet"],
+                description: "Suppress everything on stdout and report the answer through the exit status only.",
+            },
+            GlobalOption {
+                names: &["-v", "--verbose"],
+                description: "Show the network, mask and broadcast in binary when displaying a CIDR.",
+            },
+            GlobalOption {
+                names: &["--wildcard"],
+                description: "Read an ambiguous dotted mask such as 0.0.0.0 as a wildcard mask.",
+            },
+        ]
+    }
+
+    pub fn exit_statuses() -> [(i32, &'static str); 3] {
+        [
+            (0, "The answer is true or the input is valid."),
+            (1, "The answer is false, the input is invalid or an input file could not be read."),
+            (2, "The command line could not be parsed."),
+        ]
+    }
 }
 
-impl Subnet {
-    pub fn new(network: Ipv4Addr, prefix: u8) -> Result<Self, IpCalculatorError> {
-        if prefix > 32 {
-            return Err(IpCalculatorError::InvalidPrefix(
-                "Prefix cannot exceed 32".to_string(),
-            ));
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOption {
+    pub names: Vec<&'static str>,
+    pub value: Option<&'static str>,
+}
 
-        let mask = Ipv4Addr::from(if prefix == 32 {
-            u32::MAX
-        } else {
-            !(u32::MAX >> prefix)
-        });
-        let network_u32 = u32::from(network) & u32::from(mask);
-        let broadcast_u32 = network_u32 | !u32::from(mask);
+impl CommandOption {
+    pub fn takes_file(&self) -> bool {
+        self.value.is_some_and(|value| value.contains("file") || value.contains("csv"))
+    }
+}
 
-        let (first_usable, last_usable) = if prefix >= 31 {
-            (
-                Some(Ipv4Addr::from(network_u32)),
-                Some(Ipv4Addr::from(broadcast_u32)),
-            )
-        } else {
-            (
-                Some(Ipv4Addr::from(network_u32 + 1)),
-                Some(Ipv4Addr::from(broadcast_u32 - 1)),
-            )
-        };
 
-        let num_hosts = match prefix {
-            32 => 1,
-            31 => 2,
-            0 => u32::MAX - 1,
-            _ => (1u32 << (32 - prefix)) - 2,
-        };
+impl ToJson for CommandHelp {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("name", self.name.into()),
+            ("aliases", self.aliases.to_vec().into()),
+            ("short_desc", self.short_desc.into()),
+            ("long_desc", self.long_desc.into()),
+            ("usage", self.usage.into()),
+            ("examples", self.examples.to_vec().into()),
+        ])
+    }
+}
 
-        Ok(Subnet {
-            network: Ipv4Addr::from(network_u32),
-            broadcast: Ipv4Addr::from(broadcast_u32),
-            first_usable,
-            last_usable,
-            prefix,
-            mask,
-            num_hosts,
-        })
+pub trait IpFamily: Copy + Eq + Ord + Hash + fmt::Display + fmt::Debug + FromStr {
+    type Count: Copy + Eq + Ord + Hash + fmt::Display + fmt::Debug + Into<u128>;
+
+    const BITS: u8;
+    const HAS_BROADCAST: bool;
+
+    fn to_bits(self) -> u128;
+    fn from_bits(bits: u128) -> Self;
+    fn to_count(count: u128) -> Self::Count;
+    fn from_ip(ip: IpAddr) -> Option<Self>;
+
+    fn max_bits() -> u128 {
+        u128::MAX >> (128 - Self::BITS as u32)
     }
 
-    pub fn contains_ip(&self, ip: Ipv4Addr) -> bool {
-        let network_start = u32::from(self.network);
-        let network_end = u32::from(self.broadcast);
-        let ip_u32 = u32::from(ip);
-        ip_u32 >= network_start && ip_u32 <= network_end
+    fn host_bits(prefix: u8) -> u128 {
+        Self::max_bits().checked_shr(prefix as u32).unwrap_or(0)
     }
 
-    pub fn get_available_hosts(&self) -> Result<Vec<Ipv4Addr>, IpCalculatorError> {
-        let first = self.first_usable.ok_or_else(|| {
-            IpCalculatorError::SubnetError("No first usable address available".to_string())
-        })?;
-        let last = self.last_usable.ok_or_else(|| {
-            IpCalculatorError::SubnetError("No last usable address available".to_string())
-        })?;
+    fn mask_bits(prefix: u8) -> u128 {
+        Self::max_bits() & !Self::host_bits(prefix)
+    }
+}
 
-        Ok((u32::from(first)..=u32::from(last))
-            .map(Ipv4Addr::from)
-            .collect())
+impl IpFamily for Ipv4Addr {
+    type Count = u32;
+
+    const BITS: u8 = 32;
+    const HAS_BROADCAST: bool = true;
+
+    fn to_bits(self) -> u128 {
+        u32::from(self) as u128
     }
 
-    pub fn overlaps_with(&self, other: &Subnet) -> bool {
-        self.contains_ip(other.network)
-            || self.contains_ip(other.broadcast)
-            || other.contains_ip(self.network)
-            || other.contains_ip(self.broadcast)
+    fn from_bits(bits: u128) -> Self {
+        Ipv4Addr::from(bits as u32)
     }
-}
 
-impl Subnet {
-    pub fn to_plain_text(&self) -> String {
-        format!(
-            "Network: {}\nMask: {}\nCidr: {}\nBroadcast: {}\nFirst: {}\nLast: {}\nHosts: {}",
-            self.network,
-            self.mask,
-            self.prefix,
-            self.broadcast,
-            self.first_usable.map_or("N/A".to_string(), |ip| ip.to_string()),
-            self.last_usable.map_or("N/A".to_string(), |ip| ip.to_string()),
-            self.num_hosts
-        )
+    fn to_count(count: u128) -> u32 {
+        u32::try_from(count).unwrap_or(u32::MAX)
     }
-}
 
-impl fmt::Display for Subnet {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-                f,
-                "\x1b[1;34mNetwork:\x1b[0m {}\n\x1b[1;34mMask:\x1b[0m {}\n\x1b[1;34mCidr:\x1b[0m {}\n\x1b[1;34mBroadcast:\x1b[0m {}\n\x1b[1;34mFirst:\x1b[0m {}\n\x1b[1;34mLast:\x1b[0m {}\n\x1b[1;34mHosts:\x1b[0m {}",
-                self.network,
-                self.mask,
-                self.prefix,
-                self.broadcast,
-                self.first_usable.map_or("N/A".to_string(), |ip| ip.to_string()),
-                self.last_usable.map_or("N/A".to_string(), |ip| ip.to_string()),
-                self.num_hosts
-            )
+    fn from_ip(ip: IpAddr) -> Option<Self> {
+        match ip {
+            IpAddr::V4(ip) => Some(ip),
+            IpAddr::V6(_) => None,
+        }
     }
 }
 
-pub fn calculate_subnet(cidr: &str) -> Result<Subnet, IpCalculatorError> {
-    let parts: Vec<&str> = cidr.split('/').collect();
-    if parts.len() != 2 {
-        return Err(IpCalculatorError::InvalidCIDR(
-            "CIDR format must be IP/prefix".to_string(),
-        ));
-    }
+impl IpFamily for Ipv6Addr {
+    type Count = u128;
 
-    let ip = Ipv4Addr::from_str(parts[0])
-        .map_err(|_| IpCalculatorError::InvalidIP("Invalid IP address format".to_string()))?;
+    const BITS: u8 = 128;
+    const HAS_BROADCAST: bool = false;
 
-    let prefix: u8 = parts[1].parse().map_err(|_| {
-        IpCalculatorError::InvalidPrefix("Prefix must be a number between 0 and 32".to_string())
-    })?;
+    fn to_bits(self) -> u128 {
+        u128::from(self)
+    }
 
-    if prefix > 32 {
-        return Err(IpCalculatorError::InvalidPrefix(
-            "Prefix cannot exceed 32".to_string(),
-        ));
+    fn from_bits(bits: u128) -> Self {
+        Ipv6Addr::from(bits)
+    }
+
+    fn to_count(count: u128) -> u128 {
+        count
     }
 
-    Subnet::new(ip, prefix)
+    fn from_ip(ip: IpAddr) -> Option<Self> {
+        match ip {
+            IpAddr::V6(ip) => Some(ip),
+            IpAddr::V4(_) => None,
+        }
+    }
 }
 
-pub fn check_ip(ip: &str) -> Result<(), IpCalculatorError> {
-    Ipv4Addr::from_str(ip).map_err(|_| IpCalculatorError::IpError(
-        format!("Invalid IP address format: {} - IP addresses must be in the format xxx.xxx.xxx.xxx with values between 0 and 255", ip)
-    )).map(|_| ())
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AddressCount {
+    Exact(u128),
+    AllIpv6,
 }
 
-pub fn are_in_same_subnet(
-    ip1: Ipv4Addr,
-    ip2: Ipv4Addr,
-    mask1: Ipv4Addr,
-    mask2: Option<Ipv4Addr>,
-) -> Result<bool, IpCalculatorError> {
-    let prefix1 = mask_to_cidr(mask1)?;
-    let subnet1 = Subnet::new(ip1, prefix1)?;
+impl AddressCount {
+    pub const ZERO: AddressCount = AddressCount::Exact(0);
 
-    let mask2 = mask2.unwrap_or(mask1);
-    let prefix2 = mask_to_cidr(mask2)?;
-    let subnet2 = Subnet::new(ip2, prefix2)?;
+    pub fn from_host_bits(host_bits: u128) -> Self {
+        host_bits.checked_add(1).map_or(AddressCount::AllIpv6, AddressCount::Exact)
+    }
 
-    Ok(subnet1.contains_ip(ip2) && subnet2.contains_ip(ip1))
+    pub fn exact(self) -> Option<u128> {
+        match self {
+            AddressCount::Exact(count) => Some(count),
+            AddressCount::AllIpv6 => None,
+        }
+    }
+
+    pub fn saturating_sub(self, other: AddressCount) -> AddressCount {
+        match (self, other) {
+            (AddressCount::Exact(a), AddressCount::Exact(b)) => AddressCount::Exact(a.saturating_sub(b)),
+            (AddressCount::AllIpv6, AddressCount::Exact(0)) => AddressCount::AllIpv6,
+            (AddressCount::AllIpv6, AddressCount::Exact(b)) => AddressCount::Exact(u128::MAX - b + 1),
+            (_, AddressCount::AllIpv6) => AddressCount::ZERO,
+        }
+    }
 }
 
-pub fn generate_subnets(
-    cidr: &str,
-    new_prefix: u8,
-    filter: Option<usize>,
-    page: Option<usize>,
-) -> Result<(Vec<Subnet>, u32, usize, usize), IpCalculatorError> {
-    let subnet = calculate_subnet(cidr)?;
+impl std::ops::Add for AddressCount {
+    type Output = AddressCount;
 
-    if new_prefix > 32 {
-        return Err(IpCalculatorError::InvalidPrefix(
-            "The new prefix cannot exceed 32".to_string(),
-        ));
+    fn add(self, other: AddressCount) -> AddressCount {
+        match (self, other) {
+            (AddressCount::Exact(a), AddressCount::Exact(b)) => {
+                a.checked_add(b).map_or(AddressCount::AllIpv6, AddressCount::Exact)
+            },
+            _ => AddressCount::AllIpv6,
+        }
     }
+}
 
-    if new_prefix <= subnet.prefix {
-        return Err(IpCalculatorError::InvalidPrefix(format!(
-            "New prefix ({}) must be greater than current prefix ({})",
-            new_prefix, subnet.prefix
-        )));
+impl std::iter::Sum for AddressCount {
+    fn sum<I: Iterator<Item = AddressCount>>(iter: I) -> AddressCount {
+        iter.fold(AddressCount::ZERO, |total, count| total + count)
     }
+}
 
-    let num_subnets = 1u32
-        .checked_shl((new_prefix - subnet.prefix) as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError("Unable to calculate subnets".to_string()))?;
+impl From<u128> for AddressCount {
+    fn from(count: u128) -> Self {
+        AddressCount::Exact(count)
+    }
+}
 
-    let base_network = u32::from(subnet.network);
-    let increment = 1u32.checked_shl(32 - new_prefix as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError(
-            "Subnet increment calculation overflow".to_string(),
+impl fmt::Display for AddressCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddressCount::Exact(count) => write!(f, "{}", count),
+            AddressCount::AllIpv6 => f.write_str("340282366920938463463374607431768211456"),
+        }
+    }
+}
+
+impl From<AddressCount> for Json {
+    fn from(count: AddressCount) -> Self {
+        match count.exact() {
+            Some(count) => count.into(),
+            None => Json::String(count.to_string()),
+        }
+    }
+}
+
+pub type SubnetPage<A> = (Vec<Subnet<A>>, u128, usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subnet<A: IpFamily = Ipv4Addr> {
+    pub network: A,
+    pub mask: A,
+    pub wildcard: A,
+    pub broadcast: A,
+    pub first_usable: Option<A>,
+    pub last_usable: Option<A>,
+    pub prefix: u8,
+    pub num_hosts: A::Count,
+}
+
+impl<A: IpFamily> Subnet<A> {
+    pub fn new(network: A, prefix: u8) -> Result<Self, IpCalculatorError> {
+        if prefix > A::BITS {
+            return Err(IpCalculatorError::InvalidPrefix(
+                format!("Prefix cannot exceed {}", A::BITS),
+            ));
+        }
+
+        Ok(Self::from_bits(network.to_bits(), prefix))
+    }
+
+    pub(crate) fn from_bits(network: u128, prefix: u8) -> Self {
+        let mask_bits = A::mask_bits(prefix);
+        let host_bits = A::host_bits(prefix);
+        let network_bits = network & mask_bits;
+        let broadcast_bits = network_bits | host_bits;
+
+        let (first_usable, last_usable) = if !A::HAS_BROADCAST || prefix >= A::BITS - 1 {
+            (
+                Some(A::from_bits(network_bits)),
+                Some(A::from_bits(broadcast_bits)),
+            )
+        } else {
+            (
+                Some(A::from_bits(network_bits + 1)),
+                Some(A::from_bits(broadcast_bits - 1)),
+            )
+        };
+
+        let num_addresses = host_bits.saturating_add(1);
+        let num_hosts = if A::HAS_BROADCAST && prefix < A::BITS - 1 {
+            num_addresses - 2
+        } else {
+            num_addresses
+        };
+
+        Subnet {
+            network: A::from_bits(network_bits),
+            broadcast: A::from_bits(broadcast_bits),
+            first_usable,
+            last_usable,
+            prefix,
+            mask: A::from_bits(mask_bits),
+            wildcard: A::from_bits(host_bits),
+            num_hosts: A::to_count(num_hosts),
+        }
+    }
+
+    pub fn contains_ip(&self, ip: A) -> bool {
+        let network_start = self.network.to_bits();
+        let network_end = self.broadcast.to_bits();
+        let ip_bits = ip.to_bits();
+        ip_bits >= network_start && ip_bits <= network_end
+    }
+
+    pub fn get_available_hosts(&self) -> Result<Vec<A>, IpCalculatorError> {
+        if self.first_usable.is_none() {
+            return Err(IpCalculatorError::SubnetError("No first usable address available".to_string()));
+        }
+        if self.last_usable.is_none() {
+            return Err(IpCalculatorError::SubnetError("No last usable address available".to_string()));
+        }
+
+        Ok(self.hosts().collect())
+    }
+
+    pub fn hosts(&self) -> HostIter<A> {
+        HostIter::new(
+            self.first_usable.unwrap_or(self.network),
+            self.last_usable.unwrap_or(self.broadcast),
+        )
+    }
+
+    pub fn subnets(&self, new_prefix: u8) -> Result<SubnetIter<A>, IpCalculatorError> {
+        let num_subnets = self.subnet_count(new_prefix)?;
+        Ok(SubnetIter::new(self, new_prefix, num_subnets))
+    }
+
+    pub fn overlaps_with(&self, other: &Subnet<A>) -> bool {
+        self.contains_ip(other.network)
+            || self.contains_ip(other.broadcast)
+            || other.contains_ip(self.network)
+            || other.contains_ip(self.broadcast)
+    }
+
+    pub fn contains_subnet(&self, other: &Subnet<A>) -> bool {
+        self.prefix <= other.prefix && self.contains_ip(other.network)
+    }
+
+    pub fn common_supernet(&self, other: &Subnet<A>) -> Subnet<A> {
+        let differing = self.network.to_bits() ^ other.network.to_bits();
+        let common_bits = (differing.leading_zeros() - (128 - A::BITS as u32)) as u8;
+        let prefix = common_bits.min(self.prefix).min(other.prefix);
+        Subnet::from_bits(self.network.to_bits(), prefix)
+    }
+
+    pub fn classify(&self) -> &'static Classification {
+        special_purpose::classify(self)
+    }
+
+    pub fn address_total(&self) -> AddressCount {
+        AddressCount::from_host_bits(A::host_bits(self.prefix))
+    }
+
+    pub fn subnet_count(&self, new_prefix: u8) -> Result<u128, IpCalculatorError> {
+        if new_prefix > A::BITS {
+            return Err(IpCalculatorError::InvalidPrefix(format!(
+                "The new prefix cannot exceed {}",
+                A::BITS
+            )));
+        }
+
+        if new_prefix <= self.prefix {
+            return Err(IpCalculatorError::InvalidPrefix(format!(
+                "New prefix ({}) must be greater than current prefix ({})",
+                new_prefix, self.prefix
+            )));
+        }
+
+        let shift = new_prefix - self.prefix;
+        if shift >= A::BITS {
+            return Err(IpCalculatorError::SubnetError("Unable to calculate subnets".to_string()));
+        }
+        Ok(1u128 << shift)
+    }
+
+    pub fn nth_subnet(&self, new_prefix: u8, index: u128) -> Result<Subnet<A>, IpCalculatorError> {
+        let num_subnets = self.subnet_count(new_prefix)?;
+        if index >= num_subnets {
+            return Err(IpCalculatorError::SubnetError(format!(
+                "Subnet index {} exceeds available subnets ({})",
+                index, num_subnets
+            )));
+        }
+
+        let mut subnets = SubnetIter::new(self, new_prefix, num_subnets);
+        subnets.seek(index);
+        subnets.next().ok_or_else(|| IpCalculatorError::SubnetError(
+            "Network address calculation overflow".to_string(),
+        ))
+    }
+
+    pub fn subnet_page(
+        &self,
+        new_prefix: u8,
+        filter: Option<usize>,
+        page: Option<usize>,
+    ) -> Result<SubnetPage<A>, IpCalculatorError> {
+        let num_subnets = self.subnet_count(new_prefix)?;
+
+        let page_size = 4;
+        let max_to_show = std::cmp::min(
+            usize::try_from(num_subnets).unwrap_or(usize::MAX),
+            filter.unwrap_or(usize::MAX),
+        );
+        let total_pages = max_to_show.div_ceil(page_size);
+
+        let page_number = match page {
+            Some(_) if total_pages == 0 => 0,
+            Some(p) if p >= total_pages => total_pages - 1,
+            Some(p) => p,
+            None => 0,
+        };
+
+        let start_index = page_number * page_size;
+        let mut subnets: Vec<Subnet<A>> = Vec::new();
+        let end_index = std::cmp::min(start_index + page_size, max_to_show);
+
+        let mut children = self.subnets(new_prefix)?;
+        children.seek(start_index as u128);
+        for new_subnet in children.take(end_index.saturating_sub(start_index)) {
+
+            if subnets.iter().any(|existing| existing.overlaps_with(&new_subnet)) {
+                return Err(IpCalculatorError::SubnetError(
+                    format!("Generated subnet {} overlaps with existing subnets - this should not happen!",
+                        new_subnet.network
+                    )
+                ));
+            }
+
+            subnets.push(new_subnet);
+        }
+
+        Ok((subnets, num_subnets, total_pages, page_number))
+    }
+
+    pub fn all_subnets(
+        &self,
+        new_prefix: u8,
+        filter: Option<usize>,
+    ) -> Result<Vec<Subnet<A>>, IpCalculatorError> {
+        Ok(self.subnets(new_prefix)?
+            .take(filter.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    pub fn find_range(
+        &self,
+        range_size: usize,
+        exclusions: &[A],
+    ) -> Result<(A, A), IpCalculatorError> {
+        if range_size == 0 {
+            return Err(IpCalculatorError::InvalidRange(
+                "Range size must be greater than 0".to_string(),
+            ));
+        }
+
+        let first = self.first_usable.ok_or_else(|| {
+            IpCalculatorError::SubnetError("No first usable address available".to_string())
+        })?.to_bits();
+        let last = self.last_usable.ok_or_else(|| {
+            IpCalculatorError::SubnetError("No last usable address available".to_string())
+        })?.to_bits();
+
+        let mut excluded: Vec<u128> = exclusions
+            .iter()
+            .map(|ip| ip.to_bits())
+            .filter(|ip| (first..=last).contains(ip))
+            .collect();
+        excluded.sort_unstable();
+        excluded.dedup();
+
+        let wanted = range_size as u128 - 1;
+        let mut any_available = false;
+        let mut cursor = Some(first);
+
+        for ip in excluded {
+            let Some(start) = cursor else { break };
+            if ip > start {
+                any_available = true;
+                if ip - 1 - start >= wanted {
+                    return Ok((A::from_bits(start), A::from_bits(start + wanted)));
+                }
+            }
+            cursor = if ip == last { None } else { Some(ip + 1) };
+        }
+
+        if let Some(start) = cursor {
+            any_available = true;
+            if last - start >= wanted {
+                return Ok((A::from_bits(start), A::from_bits(start + wanted)));
+            }
+        }
+
+        if !any_available {
+            return Err(IpCalculatorError::RangeError(
+                "No available IPs in subnet".to_string(),
+            ));
+        }
+
+        Err(IpCalculatorError::InvalidRange("No suitable range found".to_string()))
+    }
+}
+
+impl<A: IpFamily> fmt::Display for Subnet<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl<A: IpFamily> ToJson for Subnet<A> {
+    fn to_json(&self) -> Json {
+        let mut json = Json::object([
+            ("cidr", Json::string(self)),
+            ("network", Json::string(self.network)),
+            ("prefix", self.prefix.into()),
+            ("mask", Json::string(self.mask)),
+        ]);
+        if A::HAS_BROADCAST {
+            json.push("wildcard", Json::string(self.wildcard));
+            json.push("broadcast", Json::string(self.broadcast));
+            json.push("first_usable", self.first_usable.map(|ip| ip.to_string()));
+            json.push("last_usable", self.last_usable.map(|ip| ip.to_string()));
+            json.push("hosts", Into::<u128>::into(self.num_hosts));
+        } else {
+            json.push("first", Json::string(self.network));
+            json.push("last", Json::string(self.broadcast));
+            json.push("addresses", self.address_total());
+        }
+        json.push("classification", self.classify().to_json());
+        json
+    }
+}
+
+impl<A: IpFamily> Ord for Subnet<A> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.network
+            .cmp(&other.network)
+            .then(self.prefix.cmp(&other.prefix))
+    }
+}
+
+impl<A: IpFamily> PartialOrd for Subnet<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: IpFamily> FromStr for Subnet<A> {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (ip, mask) = match input.split_once('/') {
+            Some(parts) => parts,
+            None => {
+                let mut parts = input.split_whitespace();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(ip), Some(mask), None) => (ip, mask),
+                    _ => return Err(IpCalculatorError::InvalidCIDR(
+                        "CIDR format must be IP/prefix, IP/mask or IP mask".to_string(),
+                    )),
+                }
+            }
+        };
+
+        let ip = A::from_str(ip.trim())
+            .map_err(|_| IpCalculatorError::InvalidIP("Invalid IP address format".to_string()))?;
+
+        let mask = mask.trim();
+        let prefix = if mask.chars().all(|c| c.is_ascii_digit()) {
+            let prefix: u8 = mask.parse().map_err(|_| {
+                IpCalculatorError::InvalidPrefix(format!("Prefix must be a number between 0 and {}", A::BITS))
+            })?;
+            if prefix > A::BITS {
+                return Err(IpCalculatorError::InvalidPrefix(
+                    format!("Prefix cannot exceed {}", A::BITS),
+                ));
+            }
+            prefix
+        } else {
+            let mask = A::from_str(mask)
+                .map_err(|_| IpCalculatorError::InvalidMask(format!("Invalid mask format: {}", mask)))?;
+            mask_or_wildcard_to_cidr(mask)?
+        };
+
+        Subnet::new(ip, prefix)
+    }
+}
+
+pub fn parse_cidr<A: IpFamily>(cidr: &str) -> Result<Subnet<A>, IpCalculatorError> {
+    if !cidr.contains('/') {
+        return Err(IpCalculatorError::InvalidCIDR(
+            "CIDR format must be IP/prefix".to_string(),
+        ));
+    }
+
+    cidr.parse()
+}
+
+pub fn calculate_subnet(cidr: &str) -> Result<Subnet, IpCalculatorError> {
+    parse_cidr(cidr)
+}
+
+pub fn is_ipv6(input: &str) -> bool {
+    input.contains(':')
+}
+
+pub fn check_ip(ip: &str) -> Result<(), IpCalculatorError> {
+    Ipv4Addr::from_str(ip).map_err(|_| IpCalculatorError::IpError(
+        format!("Invalid IP address format: {} - IP addresses must be in the format xxx.xxx.xxx.xxx with values between 0 and 255", ip)
+    )).map(|_| ())
+}
+
+pub fn are_in_same_subnet<A: IpFamily>(
+    ip1: A,
+    ip2: A,
+    mask1: A,
+    mask2: Option<A>,
+) -> Result<bool, IpCalculatorError> {
+    let prefix1 = mask_to_cidr(mask1)?;
+    let subnet1 = Subnet::new(ip1, prefix1)?;
+
+    let mask2 = mask2.unwrap_or(mask1);
+    let prefix2 = mask_to_cidr(mask2)?;
+    let subnet2 = Subnet::new(ip2, prefix2)?;
+
+    Ok(subnet1.contains_ip(ip2) && subnet2.contains_ip(ip1))
+}
+
+pub fn generate_subnets(
+    cidr: &str,
+    new_prefix: u8,
+    filter: Option<usize>,
+    page: Option<usize>,
+) -> Result<(Vec<Subnet>, u32, usize, usize), IpCalculatorError> {
+    let subnet = calculate_subnet(cidr)?;
+    let (subnets, num_subnets, total_pages, page_number) = subnet.subnet_page(new_prefix, filter, page)?;
+    Ok((subnets, num_subnets as u32, total_pages, page_number))
+}
+
+pub fn generate_all_subnets(
+    cidr: &str,
+    new_prefix: u8,
+    filter: Option<usize>,
+) -> Result<Vec<Subnet>, IpCalculatorError> {
+    calculate_subnet(cidr)?.all_subnets(new_prefix, filter)
+}
+
+pub fn get_subnet<A: IpFamily>(cidr: &str, new_prefix: u8, index: u128) -> Result<SubnetSelection<A>, IpCalculatorError> {
+    let (subnet, total_subnets) = select_subnet::<A>(cidr, new_prefix, index)?;
+    Ok(SubnetSelection { subnet, requested_index: index, total_subnets, output_file: None })
+}
+
+fn get_subnet_in<A: IpFamily>(
+    cidr: &str,
+    new_prefix: u8,
+    index: u128,
+    output_file: Option<OutputFile>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let selection = get_subnet::<A>(cidr, new_prefix, index)?;
+    Ok(FamilyOutput::GetSubnet(SubnetSelection { output_file: output_file.map(|output| output.path), ..selection }))
+}
+
+fn select_subnet<A: IpFamily>(
+    cidr: &str,
+    new_prefix: u8,
+    index: u128,
+) -> Result<(Subnet<A>, u128), IpCalculatorError> {
+    let base_subnet = parse_cidr::<A>(cidr)
+        .map_err(|_| IpCalculatorError::InvalidCIDR(format!(
+            "Invalid CIDR format: {}", cidr
+        )))?;
+
+    if new_prefix > A::BITS {
+        return Err(IpCalculatorError::InvalidPrefix(
+            format!("The prefix must be between 0 and {}", A::BITS),
+        ));
+    }
+
+    if new_prefix <= base_subnet.prefix {
+        return Err(IpCalculatorError::InvalidPrefix(format!(
+            "New prefix ({}) must be larger than current prefix ({})",
+            new_prefix, base_subnet.prefix
+        )));
+    }
+
+    let num_subnets = base_subnet.subnet_count(new_prefix)
+        .map_err(|_| IpCalculatorError::SubnetError(
+            "Cannot calculate number of subnets - value overflow".to_string(),
         ))?;
 
-    let page_size = 4;
-    let max_to_show = std::cmp::min(num_subnets as usize, filter.unwrap_or(usize::MAX));
-    let total_pages = if max_to_show > 0 {
-        (max_to_show + page_size - 1) / page_size
+    let subnet = base_subnet.nth_subnet(new_prefix, index.min(num_subnets - 1))?;
+    Ok((subnet, num_subnets))
+}
+
+pub fn find_ip_range(
+    cidr: &str,
+    range_size: usize,
+    exclusions: Vec<Ipv4Addr>,
+) -> Result<(Ipv4Addr, Ipv4Addr), IpCalculatorError> {
+    calculate_subnet(cidr)?.find_range(range_size, &exclusions)
+}
+
+pub fn is_cidr_or_mask(input: &str) -> Result<InputType, IpCalculatorError> {
+    detect_mask_notation(input, false)
+}
+
+pub fn detect_mask_notation(input: &str, wildcard_hint: bool) -> Result<InputType, IpCalculatorError> {
+    let invalid = || IpCalculatorError::ConversionError("Invalid CIDR or mask format".to_string());
+
+    if let Ok(cidr) = input.parse::<u8>() {
+        return if cidr <= 32 { Ok(InputType::Cidr) } else { Err(invalid()) };
+    }
+
+    let mask = Ipv4Addr::from_str(input).map_err(|_| invalid())?;
+    if wildcard_hint {
+        return if is_wildcard_mask(mask) { Ok(InputType::Wildcard) } else { Err(invalid()) };
+    }
+    match mask_to_cidr(mask) {
+        Ok(_) => Ok(InputType::Mask),
+        Err(_) if is_wildcard_mask(mask) => Ok(InputType::Wildcard),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn cidr_to_mask(cidr: u8) -> Result<Ipv4Addr, IpCalculatorError> {
+    if cidr > 32 {
+        return Err(IpCalculatorError::InvalidPrefix(
+            "CIDR prefix cannot exceed 32".to_string(),
+        ));
+    }
+    Ok(Ipv4Addr::from(if cidr == 32 {
+        u32::MAX
     } else {
-        0
-    };
-    
-    let page_number = match page {
-        Some(_) if total_pages == 0 => 0,
-        Some(p) if p >= total_pages => total_pages - 1,
-        Some(p) => p,
-        None => 0,
-    };
+        !(u32::MAX >> cidr)
+    }))
+}
+
+pub fn cidr_to_wildcard(cidr: u8) -> Result<Ipv4Addr, IpCalculatorError> {
+    Ok(!cidr_to_mask(cidr)?)
+}
+
+pub fn mask_to_cidr<A: IpFamily>(mask: A) -> Result<u8, IpCalculatorError> {
+    let mask_bits = mask.to_bits();
+
+    if mask_bits == 0 {
+        return Ok(0);
+    }
+
+    if let Some(diagnostic) = MaskDiagnostic::new(mask) {
+        return Err(IpCalculatorError::NonContiguousMask(diagnostic));
+    }
+
+    Ok(mask_bits.count_ones() as u8)
+}
 
-    let start_index = page_number * page_size;
-    let mut subnets: Vec<Subnet> = Vec::new();
-    let end_index = std::cmp::min(
-        start_index + page_size,
-        max_to_show
+pub fn is_wildcard_mask<A: IpFamily>(wildcard: A) -> bool {
+    let bits = wildcard.to_bits();
+    bits & bits.wrapping_add(1) == 0
+}
+
+pub fn wildcard_to_cidr<A: IpFamily>(wildcard: A) -> Result<u8, IpCalculatorError> {
+    if !is_wildcard_mask(wildcard) {
+        return Err(IpCalculatorError::InvalidMask(
+            "Invalid wildcard mask format".to_string(),
+        ));
+    }
+    Ok(A::BITS - wildcard.to_bits().count_ones() as u8)
+}
+
+fn mask_or_wildcard_to_cidr<A: IpFamily>(mask: A) -> Result<u8, IpCalculatorError> {
+    mask_to_cidr(mask).or_else(|e| if is_wildcard_mask(mask) { wildcard_to_cidr(mask) } else { Err(e) })
+}
+
+pub fn check_mask(mask: &str) -> Result<bool, IpCalculatorError> {
+    if let Ok(cidr) = mask.parse::<u8>() {
+        return Ok(cidr <= 32);
+    }
+
+    let mask_addr = Ipv4Addr::from_str(mask)
+        .map_err(|_| IpCalculatorError::InvalidMask("Invalid mask format".to_string()))?;
+
+    Ok(mask_or_wildcard_to_cidr(mask_addr).is_ok())
+}
+
+pub fn parse_mask_or_cidr(input: &str, return_type: InputType) -> Result<MaskOrCidr, IpCalculatorError> {
+    parse_mask_or_cidr_with_hint(input, return_type, false)
+}
+
+pub fn parse_mask_or_cidr_with_hint(
+    input: &str,
+    return_type: InputType,
+    wildcard_hint: bool,
+) -> Result<MaskOrCidr, IpCalculatorError> {
+    let parse_ip = || Ipv4Addr::from_str(input).map_err(|_|
+        IpCalculatorError::MaskError(
+            format!("Invalid mask format: {}", input)
+        )
     );
 
-    for i in start_index..end_index {
-        let new_network = base_network.checked_add((i as u32) * increment).ok_or_else(|| {
-            IpCalculatorError::SubnetError(
-                "Overflow occurred while calculating subnets".to_string(),
+    let cidr = match detect_mask_notation(input, wildcard_hint)? {
+        InputType::Cidr => input.parse::<u8>().map_err(|_|
+            IpCalculatorError::InvalidPrefix(
+                format!("Invalid CIDR prefix: {}", input)
             )
-        })?;
-        
-        let new_subnet = Subnet::new(Ipv4Addr::from(new_network), new_prefix)?;
-        
-        if subnets.iter().any(|existing| existing.overlaps_with(&new_subnet)) {
-            return Err(IpCalculatorError::SubnetError(
-                format!("Generated subnet {} overlaps with existing subnets - this should not happen!", 
-                    new_subnet.network
-                )
+        )?,
+        InputType::Mask => mask_to_cidr(parse_ip()?)?,
+        InputType::Wildcard => wildcard_to_cidr(parse_ip()?)?,
+    };
+
+    match return_type {
+        InputType::Cidr => Ok(MaskOrCidr::Cidr(cidr)),
+        InputType::Mask => Ok(MaskOrCidr::Mask(cidr_to_mask(cidr)?)),
+        InputType::Wildcard => Ok(MaskOrCidr::Wildcard(cidr_to_wildcard(cidr)?)),
+    }
+}
+
+pub fn parse_mask<A: IpFamily>(input: &str) -> Result<A, IpCalculatorError> {
+    parse_mask_with_hint(input, false)
+}
+
+pub fn parse_mask_with_hint<A: IpFamily>(input: &str, wildcard_hint: bool) -> Result<A, IpCalculatorError> {
+    if let Ok(prefix) = input.parse::<u8>() {
+        if prefix > A::BITS {
+            return Err(IpCalculatorError::InvalidPrefix(
+                format!("CIDR prefix cannot exceed {}", A::BITS),
             ));
         }
-        
-        subnets.push(new_subnet);
-    }
-
-    Ok((subnets, num_subnets, total_pages, page_number))
+        return Ok(A::from_bits(A::mask_bits(prefix)));
+    }
+
+    let mask = A::from_str(input).map_err(|_|
+        IpCalculatorError::MaskError(
+            format!("Invalid mask format: {}", input)
+        )
+    )?;
+    let prefix = if wildcard_hint {
+        wildcard_to_cidr(mask)?
+    } else {
+        mask_or_wildcard_to_cidr(mask)?
+    };
+    Ok(A::from_bits(A::mask_bits(prefix)))
+}
+
+#[derive(Debug)]
+pub struct SubnetListing<A: IpFamily = Ipv4Addr> {
+    pub base: Subnet<A>,
+    pub prefix: u8,
+    pub subnets: Vec<Subnet<A>>,
+    pub total_subnets: u128,
+    pub page: usize,
+    pub total_pages: usize,
+    pub requested_page: usize,
+    pub filter: Option<usize>,
+    pub output_file: Option<String>,
+}
+
+impl<A: IpFamily> SubnetListing<A> {
+    pub fn page_clamped(&self) -> bool {
+        self.requested_page >= self.total_pages && self.total_pages > 0
+    }
+
+    pub fn index_of(&self, subnet: &Subnet<A>) -> u128 {
+        (subnet.network.to_bits() - self.base.network.to_bits()) >> (A::BITS - self.prefix)
+    }
+}
+
+#[derive(Debug)]
+pub struct SubnetSelection<A: IpFamily = Ipv4Addr> {
+    pub subnet: Subnet<A>,
+    pub requested_index: u128,
+    pub total_subnets: u128,
+    pub output_file: Option<String>,
+}
+
+impl<A: IpFamily> SubnetSelection<A> {
+    pub fn index(&self) -> u128 {
+        self.requested_index.min(self.total_subnets - 1)
+    }
+
+    pub fn clamped(&self) -> bool {
+        self.requested_index >= self.total_subnets
+    }
+}
+
+#[derive(Debug)]
+pub struct SameSubnetCheck {
+    pub ip1: IpAddr,
+    pub ip2: IpAddr,
+    pub mask1: IpAddr,
+    pub mask2: Option<IpAddr>,
+    pub same_subnet: bool,
+}
+
+#[derive(Debug)]
+pub struct MaskCheck {
+    pub mask: String,
+    pub notation: InputType,
+    pub prefix: u8,
+    pub netmask: Ipv4Addr,
+    pub wildcard: Ipv4Addr,
+}
+
+#[derive(Debug)]
+pub enum RouteMatch {
+    V4(Ipv4Addr, Option<Route<Ipv4Addr>>),
+    V6(Ipv6Addr, Option<Route<Ipv6Addr>>),
+}
+
+#[derive(Debug)]
+pub struct Classified {
+    pub input: String,
+    pub classification: &'static Classification,
+}
+
+#[derive(Debug)]
+pub struct Conversion {
+    pub input: String,
+    pub ip: Ipv4Addr,
+    pub mask: Option<(&'static str, u8)>,
+}
+
+#[derive(Debug)]
+pub struct ReverseDnsReport<A: IpFamily = Ipv4Addr> {
+    pub subnet: Subnet<A>,
+    pub zones: Vec<String>,
+    pub classless_zone: Option<String>,
+    pub cname_records: Vec<CnameRecord>,
+    pub ptr_owners: Vec<(A, String)>,
+    pub total_addresses: AddressCount,
+}
+
+#[derive(Debug)]
+pub enum ArithmeticResult<A: IpFamily = Ipv4Addr> {
+    Address(A),
+    Network(Subnet<A>),
+    Distance(i128),
+}
+
+impl<A: IpFamily> fmt::Display for ArithmeticResult<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArithmeticResult::Address(ip) => write!(f, "{}", ip),
+            ArithmeticResult::Network(subnet) => write!(f, "{}", subnet),
+            ArithmeticResult::Distance(distance) => write!(f, "{}", distance),
+        }
+    }
+}
+
+impl<A: IpFamily> ToJson for ArithmeticResult<A> {
+    fn to_json(&self) -> Json {
+        match self {
+            ArithmeticResult::Distance(distance) => (*distance).into(),
+            result => Json::string(result),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetOutcome<A: IpFamily = Ipv4Addr> {
+    Set(IpSet<A>),
+    Subset(bool),
+}
+
+#[derive(Debug)]
+pub enum FamilyOutput<A: IpFamily = Ipv4Addr> {
+    Subnets(SubnetListing<A>),
+    GetSubnet(SubnetSelection<A>),
+    FindRange {
+        cidr: String,
+        size: usize,
+        start: A,
+        end: A,
+    },
+    Display {
+        subnet: Subnet<A>,
+        verbose: bool,
+        output_file: Option<String>,
+    },
+    RangeToCidr {
+        range: IpRange<A>,
+        subnets: Vec<Subnet<A>>,
+        output_file: Option<String>,
+    },
+    Set {
+        operation: SetOperation,
+        outcome: SetOutcome<A>,
+    },
+    Aggregate {
+        input_count: usize,
+        summaries: Vec<Summary<A>>,
+        summarized: bool,
+    },
+    Supernet {
+        supernet: Subnet<A>,
+        inputs: usize,
+        extra_addresses: AddressCount,
+    },
+    Exclude {
+        base: Subnet<A>,
+        remaining: Vec<Subnet<A>>,
+        output_file: Option<String>,
+    },
+    Vlsm {
+        parent: Subnet<A>,
+        allocations: Vec<Allocation<A>>,
+    },
+    Arithmetic {
+        operation: ArithmeticOperation,
+        operands: Vec<String>,
+        result: ArithmeticResult<A>,
+    },
+    ReverseDns(ReverseDnsReport<A>),
+    PtrZone {
+        zone: ZoneFile<A>,
+        output_file: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub enum CommandOutput {
+    V4(FamilyOutput<Ipv4Addr>),
+    V6(FamilyOutput<Ipv6Addr>),
+    SameSubnet(SameSubnetCheck),
+    CheckIp(String),
+    CheckMask(MaskCheck),
+    Lookup {
+        routes_loaded: usize,
+        matches: Vec<RouteMatch>,
+    },
+    Classify(Vec<Classified>),
+    Convert(Vec<Conversion>),
+}
+
+impl CommandOutput {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandOutput::SameSubnet(check) if !check.same_subnet => 1,
+            CommandOutput::V4(FamilyOutput::Set { outcome: SetOutcome::Subset(false), .. })
+            | CommandOutput::V6(FamilyOutput::Set { outcome: SetOutcome::Subset(false), .. }) => 1,
+            _ => 0,
+        }
+    }
+}
+
+pub fn execute_command(command: Command) -> Result<CommandOutput, IpCalculatorError> {
+    Ok(match command {
+        Command::Subnets { cidr, prefix, filter, page, output_file } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(subnets_in::<Ipv6Addr>(&cidr, prefix, filter, page, output_file)?)
+            } else {
+                CommandOutput::V4(subnets_in::<Ipv4Addr>(&cidr, prefix, filter, page, output_file)?)
+            }
+        },
+        Command::GetSubnet { cidr, prefix, index, output_file } => {
+            let failed = |e: IpCalculatorError| IpCalculatorError::SubnetError(
+                format!("Failed to get subnet {}: {}", index, e)
+            );
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(get_subnet_in::<Ipv6Addr>(&cidr, prefix, index, output_file).map_err(failed)?)
+            } else {
+                CommandOutput::V4(get_subnet_in::<Ipv4Addr>(&cidr, prefix, index, output_file).map_err(failed)?)
+            }
+        },
+        Command::SameSubnet { ip1, ip2, mask1, mask2 } => CommandOutput::SameSubnet(SameSubnetCheck {
+            ip1,
+            ip2,
+            mask1,
+            mask2,
+            same_subnet: same_subnet(ip1, ip2, mask1, mask2)?,
+        }),
+        Command::CheckIP { ip } => {
+            check_ip(&ip).map_err(|_| IpCalculatorError::InvalidIP(
+                format!("Invalid IP address format: {}", ip)
+            ))?;
+            CommandOutput::CheckIp(ip)
+        },
+        Command::CheckMask { mask, wildcard } => {
+            let (notation, prefix) = mask_details(&mask, wildcard)?;
+            CommandOutput::CheckMask(MaskCheck {
+                mask,
+                notation,
+                prefix,
+                netmask: cidr_to_mask(prefix)?,
+                wildcard: cidr_to_wildcard(prefix)?,
+            })
+        },
+        Command::FindRange { cidr, range_size, exclusions } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(find_range_in::<Ipv6Addr>(cidr, range_size, &exclusions)?)
+            } else {
+                CommandOutput::V4(find_range_in::<Ipv4Addr>(cidr, range_size, &exclusions)?)
+            }
+        },
+        Command::Display { cidr, verbose, output_file } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(display_in::<Ipv6Addr>(&cidr, verbose, output_file)?)
+            } else {
+                CommandOutput::V4(display_in::<Ipv4Addr>(&cidr, verbose, output_file)?)
+            }
+        },
+        Command::Set { operation, left, right } => {
+            let ipv6 = is_ipv6(&left) || right.as_deref().is_some_and(is_ipv6);
+            if ipv6 {
+                CommandOutput::V6(set_operation::<Ipv6Addr>(operation, &left, right.as_deref())?)
+            } else {
+                CommandOutput::V4(set_operation::<Ipv4Addr>(operation, &left, right.as_deref())?)
+            }
+        },
+        Command::Aggregate { prefixes, max_entries } => {
+            if is_ipv6(&prefixes) {
+                CommandOutput::V6(aggregate_in::<Ipv6Addr>(&prefixes, max_entries)?)
+            } else {
+                CommandOutput::V4(aggregate_in::<Ipv4Addr>(&prefixes, max_entries)?)
+            }
+        },
+        Command::Supernet { prefixes } => {
+            if is_ipv6(&prefixes) {
+                CommandOutput::V6(supernet_in::<Ipv6Addr>(&prefixes)?)
+            } else {
+                CommandOutput::V4(supernet_in::<Ipv4Addr>(&prefixes)?)
+            }
+        },
+        Command::Exclude { cidr, excluded, output_file } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(exclude_in::<Ipv6Addr>(&cidr, &excluded, output_file)?)
+            } else {
+                CommandOutput::V4(exclude_in::<Ipv4Addr>(&cidr, &excluded, output_file)?)
+            }
+        },
+        Command::Vlsm { cidr, requirements } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(vlsm_in::<Ipv6Addr>(&cidr, &requirements)?)
+            } else {
+                CommandOutput::V4(vlsm_in::<Ipv4Addr>(&cidr, &requirements)?)
+            }
+        },
+        Command::Lookup { routes, ips } => {
+            let (v4_routes, v6_routes) = load_routes(&routes)?;
+            let matches = ips
+                .into_iter()
+                .map(|ip| match ip {
+                    IpAddr::V4(ip) => RouteMatch::V4(ip, v4_routes.lookup(ip).cloned()),
+                    IpAddr::V6(ip) => RouteMatch::V6(ip, v6_routes.lookup(ip).cloned()),
+                })
+                .collect();
+            CommandOutput::Lookup { routes_loaded: v4_routes.len() + v6_routes.len(), matches }
+        },
+        Command::Classify { inputs } => {
+            let mut results = Vec::new();
+            for input in inputs {
+                let classification = if is_ipv6(&input) {
+                    classify_input::<Ipv6Addr>(&input)
+                } else {
+                    classify_input::<Ipv4Addr>(&input)
+                }?;
+                results.push(Classified { input, classification });
+            }
+            CommandOutput::Classify(results)
+        },
+        Command::Convert { values } => {
+            let mut results = Vec::new();
+            for input in values {
+                let ip = parse_ipv4_any(&input)?;
+                results.push(Conversion { input, ip, mask: mask_kind(ip) });
+            }
+            CommandOutput::Convert(results)
+        },
+        Command::Arithmetic { operation, operands } => {
+            let first = operands.first().map(String::as_str).unwrap_or_default();
+            if is_ipv6(first) {
+                let result = calculate_arithmetic::<Ipv6Addr>(&operation, &operands)?;
+                CommandOutput::V6(FamilyOutput::Arithmetic { operation, operands, result })
+            } else {
+                let result = calculate_arithmetic::<Ipv4Addr>(&operation, &operands)?;
+                CommandOutput::V4(FamilyOutput::Arithmetic { operation, operands, result })
+            }
+        },
+        Command::ReverseDns { cidr, filter } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(FamilyOutput::ReverseDns(reverse_dns::<Ipv6Addr>(&cidr, filter)?))
+            } else {
+                CommandOutput::V4(FamilyOutput::ReverseDns(reverse_dns::<Ipv4Addr>(&cidr, filter)?))
+            }
+        },
+        Command::PtrZone { cidr, template, names, nameservers, output_file } => {
+            if is_ipv6(&cidr) {
+                CommandOutput::V6(ptr_zone_in::<Ipv6Addr>(&cidr, &template, names.as_deref(), &nameservers, output_file)?)
+            } else {
+                CommandOutput::V4(ptr_zone_in::<Ipv4Addr>(&cidr, &template, names.as_deref(), &nameservers, output_file)?)
+            }
+        },
+        Command::RangeToCidr { range, output_file } => {
+            if is_ipv6(&range) {
+                CommandOutput::V6(range_in::<Ipv6Addr>(&range, output_file)?)
+            } else {
+                CommandOutput::V4(range_in::<Ipv4Addr>(&range, output_file)?)
+            }
+        }
+    })
 }
 
-pub fn generate_all_subnets(
+fn display_in<A: IpFamily>(
     cidr: &str,
-    new_prefix: u8,
-    filter: Option<usize>,
-) -> Result<Vec<Subnet>, IpCalculatorError> {
-    let subnet = calculate_subnet(cidr)?;
-
-    if new_prefix > 32 {
-        return Err(IpCalculatorError::InvalidPrefix(
-            "The new prefix cannot exceed 32".to_string(),
-        ));
-    }
+    verbose: bool,
+    output_file: Option<OutputFile>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let subnet = parse_cidr::<A>(cidr).map_err(|e| IpCalculatorError::InvalidCIDR(
+        format!("Failed to calculate subnet for {}: {}", cidr, e)
+    ))?;
+    Ok(FamilyOutput::Display { subnet, verbose, output_file: output_file.map(|output| output.path) })
+}
 
-    if new_prefix <= subnet.prefix {
-        return Err(IpCalculatorError::InvalidPrefix(format!(
-            "New prefix ({}) must be greater than current prefix ({})",
-            new_prefix, subnet.prefix
-        )));
+fn aggregate_in<A: IpFamily>(prefixes: &str, max_entries: Option<usize>) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let prefixes: Vec<Subnet<A>> = parse_prefix_list(prefixes)?;
+    if prefixes.is_empty() {
+        return Err(IpCalculatorError::ArgumentsError("No prefixes to aggregate".to_string()));
     }
 
-    let num_subnets = 1u32
-        .checked_shl((new_prefix - subnet.prefix) as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError("Unable to calculate subnets".to_string()))?;
-
-    let base_network = u32::from(subnet.network);
-    let increment = 1u32.checked_shl(32 - new_prefix as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError(
-            "Subnet increment calculation overflow".to_string(),
-        ))?;
-
-    let count = std::cmp::min(num_subnets as usize, filter.unwrap_or(num_subnets as usize));
-    let mut subnets: Vec<Subnet> = Vec::with_capacity(count);
-
-    for i in 0..count {
-        let new_network = base_network.checked_add((i as u32) * increment).ok_or_else(|| {
-            IpCalculatorError::SubnetError(
-                "Overflow occurred while calculating subnets".to_string(),
-            )
-        })?;
+    let summaries = match max_entries {
+        None => aggregate(&prefixes)
+            .into_iter()
+            .map(|subnet| Summary { subnet, extra_addresses: AddressCount::ZERO })
+            .collect(),
+        Some(max_entries) => summarize(&prefixes, max_entries)?,
+    };
+    Ok(FamilyOutput::Aggregate { input_count: prefixes.len(), summaries, summarized: max_entries.is_some() })
+}
 
-        let new_subnet = Subnet::new(Ipv4Addr::from(new_network), new_prefix)?;
+fn supernet_in<A: IpFamily>(prefixes: &str) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let prefixes: Vec<Subnet<A>> = parse_prefix_list(prefixes)?;
+    let result = supernet(&prefixes).ok_or_else(|| IpCalculatorError::ArgumentsError(
+        "No addresses or prefixes given".to_string()
+    ))?;
+
+    Ok(FamilyOutput::Supernet {
+        supernet: result,
+        inputs: prefixes.len(),
+        extra_addresses: supernet_extra_addresses(&result, &prefixes),
+    })
+}
 
-        if subnets.iter().any(|existing| existing.overlaps_with(&new_subnet)) {
-            return Err(IpCalculatorError::SubnetError(
-                format!("Generated subnet {} overlaps with existing subnets - this should not happen!",
-                    new_subnet.network
-                )
-            ));
-        }
+fn exclude_in<A: IpFamily>(
+    cidr: &str,
+    excluded: &str,
+    output_file: Option<OutputFile>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let base = parse_cidr::<A>(cidr)?;
+    let excluded = list_items(excluded)
+        .map(|item| item.parse::<IpRange<A>>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let remaining = address_exclude(&base, &excluded);
+    Ok(FamilyOutput::Exclude { base, remaining, output_file: output_file.map(|output| output.path) })
+}
 
-        subnets.push(new_subnet);
+fn vlsm_in<A: IpFamily>(cidr: &str, requirements: &str) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let parent = parse_cidr::<A>(cidr)?;
+    let requirements = parse_requirements(requirements)?;
+    if requirements.is_empty() {
+        return Err(IpCalculatorError::ArgumentsError("No subnet requirements given".to_string()));
     }
-
-    Ok(subnets)
+    let allocations = plan_vlsm(&parent, &requirements)?;
+    Ok(FamilyOutput::Vlsm { parent, allocations })
 }
 
-pub fn get_subnet(cidr: &str, new_prefix: u8, index: u32) -> Result<(), IpCalculatorError> {
-    let base_subnet = calculate_subnet(cidr)
-        .map_err(|_| IpCalculatorError::InvalidCIDR(format!(
-            "Invalid CIDR format: {}", cidr
-        )))?;
+pub fn vlsm_totals<A: IpFamily>(parent: &Subnet<A>, allocations: &[Allocation<A>]) -> (u128, AddressCount) {
+    let allocated: AddressCount = allocations.iter().map(|a| a.subnet.address_total()).sum();
+    let wasted = allocations.iter().fold(0u128, |total, a| total.saturating_add(a.wasted));
+    (wasted, parent.address_total().saturating_sub(allocated))
+}
 
-    if new_prefix > 32 {
-        return Err(IpCalculatorError::InvalidPrefix(
-            "The prefix must be between 0 and 32".to_string(),
-        ));
+fn mask_kind(ip: Ipv4Addr) -> Option<(&'static str, u8)> {
+    if let Ok(prefix) = mask_to_cidr(ip) {
+        Some(("netmask", prefix))
+    } else {
+        wildcard_to_cidr(ip).ok().map(|prefix| ("wildcard mask", prefix))
     }
+}
 
-    if new_prefix <= base_subnet.prefix {
-        return Err(IpCalculatorError::InvalidPrefix(format!(
-            "New prefix ({}) must be larger than current prefix ({})",
-            new_prefix, base_subnet.prefix
-        )));
+fn classify_input<A: IpFamily>(input: &str) -> Result<&'static Classification, IpCalculatorError> {
+    match A::from_str(input) {
+        Ok(ip) => Ok(special_purpose::classify_ip(ip)),
+        Err(_) => Ok(input.parse::<Subnet<A>>()?.classify()),
     }
+}
 
-    let num_subnets = 1u32.checked_shl((new_prefix - base_subnet.prefix) as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError(
-            "Cannot calculate number of subnets - value overflow".to_string(),
-        ))?;
-
-    let actual_index = if index >= num_subnets {
-        println!(
-            "Warning: Requested subnet index {} exceeds available subnets ({})",
-            index, num_subnets
-        );
-        num_subnets - 1
-    } else {
-        index
+fn calculate_arithmetic<A: IpFamily>(
+    operation: &ArithmeticOperation,
+    operands: &[String],
+) -> Result<ArithmeticResult<A>, IpCalculatorError> {
+    let (first, second) = match (operands, operation) {
+        ([first, second], _) => (first, second.as_str()),
+        ([first], ArithmeticOperation::NextNetwork | ArithmeticOperation::PreviousNetwork) => (first, ""),
+        _ => return Err(IpCalculatorError::ArgumentsError(
+            format!("The {} operation needs exactly two operands", operation)
+        )),
     };
+    let parse_ip = |input: &str| A::from_str(input).map_err(|_|
+        IpCalculatorError::InvalidIP(format!("Invalid IP address: {}", input))
+    );
+    let parse_number = |input: &str| input.parse::<i128>().map_err(|_|
+        IpCalculatorError::ArgumentsError(format!("Invalid number: {}", input))
+    );
 
-    let base_network = u32::from(base_subnet.network);
-    let increment = 1u32.checked_shl(32 - new_prefix as u32)
-        .ok_or_else(|| IpCalculatorError::SubnetError(
-            "Subnet increment calculation overflow".to_string(),
-        ))?;
-
-    let new_network = base_network.checked_add(actual_index * increment)
-        .ok_or_else(|| IpCalculatorError::SubnetError(
-            "Network address calculation overflow".to_string(),
-        ))?;
-
-    let subnet = Subnet::new(Ipv4Addr::from(new_network), new_prefix)?;
-    println!("{}", subnet);
-    Ok(())
-}
-
-pub fn find_ip_range(
-    cidr: &str,
-    range_size: usize,
-    exclusions: Vec<Ipv4Addr>,
-) -> Result<(Ipv4Addr, Ipv4Addr), IpCalculatorError> {
-    if range_size == 0 {
-        return Err(IpCalculatorError::InvalidRange(
-            "Range size must be greater than 0".to_string(),
-        ));
+    match operation {
+        ArithmeticOperation::Add => Ok(ArithmeticResult::Address(add_offset(parse_ip(first)?, parse_number(second)?)?)),
+        ArithmeticOperation::Subtract => {
+            let offset = parse_number(second)?.checked_neg().ok_or_else(|| IpCalculatorError::AddressOverflow(
+                format!("Offset {} is too large", second)
+            ))?;
+            Ok(ArithmeticResult::Address(add_offset(parse_ip(first)?, offset)?))
+        },
+        ArithmeticOperation::NthHost => {
+            let subnet = parse_cidr::<A>(first)?;
+            Ok(ArithmeticResult::Address(nth_host(&subnet, parse_number(second)?)?))
+        },
+        ArithmeticOperation::Distance => Ok(ArithmeticResult::Distance(distance(parse_ip(first)?, parse_ip(second)?)?)),
+        ArithmeticOperation::NextNetwork | ArithmeticOperation::PreviousNetwork => {
+            let subnet = match second {
+                "" => parse_cidr::<A>(first)?,
+                prefix => {
+                    let prefix = prefix.trim_start_matches('/').parse::<u8>().map_err(|_|
+                        IpCalculatorError::InvalidCIDR(format!("Invalid prefix length: {}", prefix))
+                    )?;
+                    Subnet::new(parse_ip(first)?, prefix)?
+                },
+            };
+            let stepped = match operation {
+                ArithmeticOperation::NextNetwork => next_network(&subnet)?,
+                _ => previous_network(&subnet)?,
+            };
+            Ok(ArithmeticResult::Network(stepped))
+        },
     }
+}
 
-    let subnet = calculate_subnet(cidr)?;
-    let available_ips: Vec<_> = subnet
-        .get_available_hosts()?
-        .into_iter()
-        .filter(|ip| !exclusions.contains(ip))
+pub fn reverse_dns<A: IpFamily>(cidr: &str, filter: Option<usize>) -> Result<ReverseDnsReport<A>, IpCalculatorError> {
+    let subnet = parse_cidr::<A>(cidr)?;
+    let ptr_owners = HostIter::new(subnet.network, subnet.broadcast)
+        .take(filter.unwrap_or(256))
+        .map(|ip| (ip, ptr_owner(&subnet, ip)))
         .collect();
 
-    if available_ips.is_empty() {
-        return Err(IpCalculatorError::RangeError(
-            "No available IPs in subnet".to_string(),
-        ));
-    }
-
-    let mut best_start = None;
-    let mut current_start = 0;
-    let mut current_len = 0;
-
-    for (i, ip) in available_ips.iter().enumerate() {
-        if i > 0 && u32::from(*ip) != u32::from(available_ips[i - 1]) + 1 {
-            current_start = i;
-            current_len = 1;
-        } else {
-            current_len += 1;
-        }
-
-        if current_len >= range_size {
-            best_start = Some(current_start);
-            break;
-        }
-    }
+    Ok(ReverseDnsReport {
+        subnet,
+        zones: reverse_zones(&subnet),
+        classless_zone: classless_zone(&subnet),
+        cname_records: classless_delegation(&subnet),
+        ptr_owners,
+        total_addresses: subnet.address_total(),
+    })
+}
 
-    best_start
-        .map(|start| (available_ips[start], available_ips[start + range_size - 1]))
-        .ok_or_else(|| IpCalculatorError::InvalidRange("No suitable range found".to_string()))
+fn ptr_zone_in<A: IpFamily>(
+    cidr: &str,
+    template: &str,
+    names: Option<&str>,
+    nameservers: &[String],
+    output_file: Option<OutputFile>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let subnet = parse_cidr::<A>(cidr)?;
+    let template = match names {
+        Some(csv) => HostnameTemplate::with_names(template, csv)?,
+        None if template.contains("{name}") => return Err(IpCalculatorError::ArgumentsError(
+            "The {name} placeholder needs a CSV of names (--names <file>)".to_string()
+        )),
+        None => HostnameTemplate::new(template),
+    };
+    let zone = ZoneFile::build(&subnet, &template, nameservers)?;
+    Ok(FamilyOutput::PtrZone { zone, output_file: output_file.map(|output| output.path) })
 }
 
-pub fn is_cidr_or_mask(input: &str) -> Result<InputType, IpCalculatorError> {
-    if check_mask(input)? {
-        if let Ok(cidr) = input.parse::<u8>() {
-            if cidr <= 32 {
-                return Ok(InputType::Cidr);
-            }
-        } else if Ipv4Addr::from_str(input).is_ok() {
-            return Ok(InputType::Mask);
-        }
-    }
-    Err(IpCalculatorError::ConversionError(
-        "Invalid CIDR or mask format".to_string(),
-    ))
+fn mask_details(mask: &str, wildcard: bool) -> Result<(InputType, u8), IpCalculatorError> {
+    let invalid = || IpCalculatorError::InvalidMask(
+        format!("Invalid subnet mask format: {}", mask)
+    );
+    let notation = match detect_mask_notation(mask, wildcard) {
+        Ok(notation) => notation,
+        Err(e @ IpCalculatorError::NonContiguousMask(_)) => return Err(e),
+        Err(_) => return Err(invalid()),
+    };
+    let cidr = parse_mask_or_cidr_with_hint(mask, InputType::Cidr, wildcard)
+        .map_err(|_| invalid())?
+        .expect_cidr();
+    Ok((notation, cidr))
 }
 
-pub fn cidr_to_mask(cidr: u8) -> Result<Ipv4Addr, IpCalculatorError> {
-    if cidr > 32 {
-        return Err(IpCalculatorError::InvalidPrefix(
-            "CIDR prefix cannot exceed 32".to_string(),
-        ));
+fn load_routes(routes: &str) -> Result<(RouteTable<Ipv4Addr>, RouteTable<Ipv6Addr>), IpCalculatorError> {
+    let (v4_routes, v6_routes) = parse_routes(routes)?;
+    if v4_routes.is_empty() && v6_routes.is_empty() {
+        return Err(IpCalculatorError::InputError("The route table is empty".to_string()));
     }
-    Ok(Ipv4Addr::from(if cidr == 32 {
-        u32::MAX
-    } else {
-        !(u32::MAX >> cidr)
-    }))
+    Ok((v4_routes, v6_routes))
 }
 
-pub fn mask_to_cidr(mask: Ipv4Addr) -> Result<u8, IpCalculatorError> {
-    let mask_u32 = u32::from(mask);
-
-    if mask_u32 == 0 {
-        return Ok(0);
-    }
+fn set_operation<A: IpFamily>(
+    operation: SetOperation,
+    left: &str,
+    right: Option<&str>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let left: IpSet<A> = left.parse()?;
+    let right: IpSet<A> = match (&operation, right) {
+        (SetOperation::Complement, _) => IpSet::new(),
+        (_, Some(right)) => right.parse()?,
+        (_, None) => return Err(IpCalculatorError::ArgumentsError(
+            format!("The {} operation needs two lists", operation)
+        )),
+    };
 
-    let inverted = !mask_u32;
+    let outcome = match operation {
+        SetOperation::Union => SetOutcome::Set(left.union(&right)),
+        SetOperation::Intersection => SetOutcome::Set(left.intersection(&right)),
+        SetOperation::Difference => SetOutcome::Set(left.difference(&right)),
+        SetOperation::Complement => SetOutcome::Set(left.complement()),
+        SetOperation::Subset => SetOutcome::Subset(left.is_subset(&right)),
+    };
+    Ok(FamilyOutput::Set { operation, outcome })
+}
 
-    if !inverted.wrapping_add(1).is_power_of_two() {
-        return Err(IpCalculatorError::InvalidMask(
-            "Invalid subnet mask format".to_string(),
-        ));
-    }
+fn range_in<A: IpFamily>(range: &str, output_file: Option<OutputFile>) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let range: IpRange<A> = range.parse()?;
+    let subnets = range.to_subnets();
+    Ok(FamilyOutput::RangeToCidr { range, subnets, output_file: output_file.map(|output| output.path) })
+}
 
-    Ok(mask_u32.count_ones() as u8)
+fn subnets_in<A: IpFamily>(
+    cidr: &str,
+    prefix: u8,
+    filter: Option<usize>,
+    page: Option<usize>,
+    output_file: Option<OutputFile>,
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let listing = list_subnets::<A>(cidr, prefix, filter, page)?;
+    Ok(FamilyOutput::Subnets(SubnetListing { output_file: output_file.map(|output| output.path), ..listing }))
 }
 
-pub fn check_mask(mask: &str) -> Result<bool, IpCalculatorError> {
-    if let Ok(cidr) = mask.parse::<u8>() {
-        return Ok(cidr <= 32);
-    }
+pub fn list_subnets<A: IpFamily>(
+    cidr: &str,
+    prefix: u8,
+    filter: Option<usize>,
+    page: Option<usize>,
+) -> Result<SubnetListing<A>, IpCalculatorError> {
+    let (base, (subnets, total_subnets, total_pages, page_number)) = parse_cidr::<A>(cidr)
+        .and_then(|subnet| subnet.subnet_page(prefix, filter, page).map(|page| (subnet, page)))
+        .map_err(|e| IpCalculatorError::SubnetError(
+            format!("Failed to generate subnets: {}", e)
+        ))?;
 
-    let mask_addr = Ipv4Addr::from_str(mask)
-        .map_err(|_| IpCalculatorError::InvalidMask("Invalid mask format".to_string()))?;
+    Ok(SubnetListing {
+        base,
+        prefix,
+        subnets,
+        total_subnets,
+        page: page_number,
+        total_pages,
+        requested_page: page.unwrap_or(0),
+        filter,
+        output_file: None,
+    })
+}
 
-    let mask_u32 = u32::from(mask_addr);
-    if mask_u32 == 0 {
-        return Ok(true);
-    }
-    let inverted = !mask_u32;
-    Ok(inverted.wrapping_add(1).is_power_of_two())
+fn same_subnet(
+    ip1: IpAddr,
+    ip2: IpAddr,
+    mask1: IpAddr,
+    mask2: Option<IpAddr>,
+) -> Result<bool, IpCalculatorError> {
+    match (ip1, ip2) {
+        (IpAddr::V4(_), IpAddr::V4(_)) => same_subnet_in::<Ipv4Addr>(ip1, ip2, mask1, mask2),
+        (IpAddr::V6(_), IpAddr::V6(_)) => same_subnet_in::<Ipv6Addr>(ip1, ip2, mask1, mask2),
+        _ => Err(IpCalculatorError::IpError(
+            "Cannot compare an IPv4 address with an IPv6 address".to_string()
+        )),
+    }
+    .map_err(|e| IpCalculatorError::SubnetError(
+        format!("Failed to compare subnets: {}", e)
+    ))
 }
 
-pub fn parse_mask_or_cidr(input: &str, return_type: InputType) -> Result<MaskOrCidr, IpCalculatorError> {
-    let input_type = is_cidr_or_mask(input)?;
-
-    match (&input_type, &return_type) {
-        (InputType::Mask, InputType::Cidr) => {
-            let mask = Ipv4Addr::from_str(input).map_err(|_| 
-                IpCalculatorError::MaskError(
-                    format!("Invalid mask format: {}", input)
-                )
-            )?;
-            let cidr = mask_to_cidr(mask)?;
-            Ok(MaskOrCidr::Cidr(cidr))
-        },
-        (InputType::Cidr, InputType::Mask) => {
-            let cidr = input.parse::<u8>().map_err(|_| 
-                IpCalculatorError::InvalidPrefix(
-                    format!("Invalid CIDR prefix: {}", input)
-                )
-            )?;
-            let mask = cidr_to_mask(cidr)?;
-            Ok(MaskOrCidr::Mask(mask))
-        },
-        (InputType::Mask, InputType::Mask) => {
-            let mask = Ipv4Addr::from_str(input).map_err(|_|
-                IpCalculatorError::MaskError(
-                    format!("Invalid mask format: {}", input)
-                )
-            )?;
-            Ok(MaskOrCidr::Mask(mask))
-        },
-        (InputType::Cidr, InputType::Cidr) => {
-            let cidr = input.parse::<u8>().map_err(|_|
-                IpCalculatorError::InvalidPrefix(
-                    format!("Invalid CIDR prefix: {}", input)
-                )
-            )?;
-            Ok(MaskOrCidr::Cidr(cidr))
-        },
+fn same_subnet_in<A: IpFamily>(
+    ip1: IpAddr,
+    ip2: IpAddr,
+    mask1: IpAddr,
+    mask2: Option<IpAddr>,
+) -> Result<bool, IpCalculatorError> {
+    let family_mismatch = || IpCalculatorError::MaskError(
+        "Mask does not belong to the same address family as the IPs".to_string()
+    );
+    let mask1 = A::from_ip(mask1).ok_or_else(family_mismatch)?;
+    let mask2 = match mask2 {
+        Some(mask) => Some(A::from_ip(mask).ok_or_else(family_mismatch)?),
+        None => None,
+    };
+    match (A::from_ip(ip1), A::from_ip(ip2)) {
+        (Some(ip1), Some(ip2)) => are_in_same_subnet(ip1, ip2, mask1, mask2),
+        _ => Err(family_mismatch()),
     }
 }
 
-pub fn execute_command(command: Command) -> Result<(), IpCalculatorError> {
-    match command {
-        Command::Subnets { cidr, prefix, filter, page, output_file } => {
-            let (subnets, total_subnets, total_pages, page_number) = generate_subnets(&cidr, prefix, filter, page)
-                .map_err(|e| IpCalculatorError::SubnetError(
-                    format!("Failed to generate subnets: {}", e)
-                ))?;
-            
-            let displayed = subnets.len();
-            let requested_page = page.unwrap_or(0);    
-            
-            for subnet in &subnets {
-                println!("{}", subnet);
-                println!("----------------------------");
-            }
-            if requested_page >= total_pages && total_pages > 0 {
-                println!("\x1b[1;33mWarning: Only {} pages available. Showing last page.\x1b[0m", total_pages);
-            }
-            println!("\nTotal subnets: {} | Subnets displayed: {}", total_subnets, displayed);
-            if total_pages > 0 {
-                println!("Page {}/{}", page_number + 1, total_pages);
-            }
-
-            if let Some(path) = output_file {
-                let all = generate_all_subnets(&cidr, prefix, filter)
-                    .map_err(|e| IpCalculatorError::SubnetError(
-                        format!("Failed to generate subnets for output file: {}", e)
-                    ))?;
-                let content = all.iter()
-                    .map(|s| s.to_plain_text())
-                    .collect::<Vec<_>>()
-                    .join("\n----------------------------\n");
-                std::fs::write(&path, content)
-                    .map_err(|e| IpCalculatorError::SubnetError(
-                        format!("Failed to write output file '{}': {}", path, e)
-                    ))?;
-                println!("Output written to {}", path);
-            }
-            
-            Ok(())
-        },
-        Command::GetSubnet { cidr, prefix, index } => {
-            get_subnet(&cidr, prefix, index)
-                .map_err(|e| IpCalculatorError::SubnetError(
-                    format!("Failed to get subnet {}: {}", index, e)
-                ))
-        },
-        Command::SameSubnet { ip1, ip2, mask1, mask2 } => {
-            let result = are_in_same_subnet(ip1, ip2, mask1, mask2)
-                .map_err(|e| IpCalculatorError::SubnetError(
-                    format!("Failed to compare subnets: {}", e)
-                ))?;
-            
-            println!(
-                "IP addresses {} and {} {} in the same subnet",
-                ip1, ip2,
-                if result { "are" } else { "are not" }
-            );
-            Ok(())
-        },
-        Command::CheckIP { ip } => {
-            check_ip(&ip).map_err(|_| IpCalculatorError::InvalidIP(
-                format!("Invalid IP address format: {}", ip)
-            ))?;
-            println!("IP address {} is valid", ip);
-            Ok(())
-        },
-        Command::CheckMask { mask } => {
-            match check_mask(&mask)? {
-                true => {
-                    println!("Subnet mask {} is valid", mask);
-                    Ok(())
-                },
-                false => Err(IpCalculatorError::InvalidMask(
-                    format!("Invalid subnet mask format: {}", mask)
-                ))
-            }
-        },
-        Command::FindRange { cidr, range_size, exclusions } => {
-            let (start, end) = find_ip_range(&cidr, range_size, exclusions)
-                .map_err(|e| IpCalculatorError::RangeError(
-                    format!("Failed to find IP range: {}", e)
-                ))?;
-            println!("Available IP range: {} - {}", start, end);
-            Ok(())
-        },
-        Command::Display { cidr } => {
-            let subnet = calculate_subnet(&cidr)
-                .map_err(|e| IpCalculatorError::InvalidCIDR(
-                    format!("Failed to calculate subnet for {}: {}", cidr, e)
-                ))?;
-            println!("{}", subnet);
-            Ok(())
-        }
-    }
+fn find_range_in<A: IpFamily>(
+    cidr: String,
+    range_size: usize,
+    exclusions: &[IpAddr],
+) -> Result<FamilyOutput<A>, IpCalculatorError> {
+    let exclusions: Vec<A> = exclusions.iter().filter_map(|ip| A::from_ip(*ip)).collect();
+    let (start, end) = parse_cidr::<A>(&cidr)
+        .and_then(|subnet| subnet.find_range(range_size, &exclusions))
+        .map_err(|e| IpCalculatorError::RangeError(
+            format!("Failed to find IP range: {}", e)
+        ))?;
+    Ok(FamilyOutput::FindRange { cidr, size: range_size, start, end })
 }
 
 #[cfg(test)]
@@ -1003,6 +2446,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_check_mask() {
         assert_eq!(check_mask("24").unwrap(), true);
         assert_eq!(check_mask("0").unwrap(), true);
@@ -1057,6 +2501,79 @@ mod tests {
         assert_eq!(result.expect_mask(), Ipv4Addr::new(255, 255, 255, 0));
     }
 
+    #[test]
+    fn test_wildcard_masks() {
+        assert!(matches!(is_cidr_or_mask("0.0.0.255").unwrap(), InputType::Wildcard));
+        assert!(matches!(is_cidr_or_mask("0.0.0.0").unwrap(), InputType::Mask));
+        assert!(matches!(detect_mask_notation("0.0.0.0", true).unwrap(), InputType::Wildcard));
+        assert!(detect_mask_notation("255.255.255.0", true).is_err());
+        assert!(matches!(is_cidr_or_mask("255.0.255.0"), Err(IpCalculatorError::NonContiguousMask(_))));
+        assert!(check_mask("0.0.15.255").unwrap());
+        assert!(!check_mask("0.0.255.0").unwrap());
+
+        assert_eq!(wildcard_to_cidr(Ipv4Addr::new(0, 0, 0, 255)).unwrap(), 24);
+        assert_eq!(cidr_to_wildcard(20).unwrap(), Ipv4Addr::new(0, 0, 15, 255));
+        assert!(wildcard_to_cidr(Ipv4Addr::new(255, 255, 255, 0)).is_err());
+
+        let result = parse_mask_or_cidr("0.0.0.63", InputType::Cidr).unwrap();
+        assert_eq!(result.expect_cidr(), 26);
+        let result = parse_mask_or_cidr("255.255.255.192", InputType::Wildcard).unwrap();
+        assert_eq!(result.expect_wildcard(), Ipv4Addr::new(0, 0, 0, 63));
+        let result = parse_mask_or_cidr_with_hint("0.0.0.0", InputType::Cidr, true).unwrap();
+        assert_eq!(result.expect_cidr(), 32);
+
+        assert_eq!(parse_mask::<Ipv4Addr>("0.0.0.255").unwrap(), Ipv4Addr::new(255, 255, 255, 0));
+        assert_eq!(parse_mask_with_hint::<Ipv4Addr>("0.0.0.0", true).unwrap(), Ipv4Addr::BROADCAST);
+        assert_eq!(parse_mask::<Ipv6Addr>("::ffff:ffff").unwrap().to_string(), "ffff:ffff:ffff:ffff:ffff:ffff::");
+    }
+
+    #[test]
+    fn test_non_contiguous_mask_diagnostic() {
+        let err = mask_to_cidr(Ipv4Addr::new(255, 255, 0, 255)).unwrap_err();
+        let IpCalculatorError::NonContiguousMask(diagnostic) = err else {
+            panic!("expected a non-contiguous mask error");
+        };
+        assert_eq!(diagnostic.position, 24);
+        assert_eq!(diagnostic.binary(), "11111111.11111111.00000000.11111111");
+        assert_eq!(diagnostic.marker(), format!("{}^", " ".repeat(27)));
+        assert_eq!(diagnostic.suggested_prefixes(), [16, 32]);
+
+        let diagnostic = MaskDiagnostic::new(Ipv4Addr::new(255, 255, 255, 253)).unwrap();
+        assert_eq!(diagnostic.position, 31);
+        assert_eq!(diagnostic.suggested_prefixes(), [30, 32]);
+
+        let diagnostic = MaskDiagnostic::new(Ipv4Addr::new(255, 0, 255, 0)).unwrap();
+        assert_eq!(diagnostic.position, 16);
+        assert!(diagnostic.to_string().contains("bit 17 of 32"));
+
+        assert!(MaskDiagnostic::new(Ipv4Addr::new(255, 255, 255, 0)).is_none());
+        assert!(MaskDiagnostic::new(Ipv4Addr::UNSPECIFIED).is_none());
+        assert!(MaskDiagnostic::new(Ipv4Addr::BROADCAST).is_none());
+
+        let v6 = MaskDiagnostic::new("ffff:0:ffff::".parse::<Ipv6Addr>().unwrap()).unwrap();
+        assert_eq!(v6.position, 32);
+        assert!(v6.binary().starts_with("1111111111111111:0000000000000000:1"));
+    }
+
+    #[test]
+    fn test_verbose_display_shows_binary() {
+        let subnet = calculate_subnet("192.168.1.0/24").unwrap();
+        let verbose = subnet.verbose().to_string();
+        assert!(verbose.starts_with(&subnet.colored().to_string()));
+        assert!(verbose.contains("11000000.10101000.00000001\x1b[0m|\x1b[33m00000000"));
+        assert!(verbose.contains("11111111.11111111.11111111\x1b[0m|\x1b[33m00000000"));
+        assert!(verbose.contains("11000000.10101000.00000001\x1b[0m|\x1b[33m11111111"));
+    }
+
+    #[test]
+    fn test_subnet_wildcard_field() {
+        let subnet: Subnet = "10.1.0.0 0.0.255.255".parse().unwrap();
+        assert_eq!(subnet.prefix, 16);
+        assert_eq!(subnet.wildcard, Ipv4Addr::new(0, 0, 255, 255));
+        assert!(subnet.to_plain_text().contains("Wildcard: 0.0.255.255"));
+        assert_eq!("10.1.0.0/0.0.0.0".parse::<Subnet>().unwrap().prefix, 0);
+    }
+
     #[test]
     fn test_are_in_same_subnet_same_mask() {
         let result = are_in_same_subnet(
@@ -1118,12 +2635,15 @@ mod tests {
 
     #[test]
     fn test_get_subnet() {
-        assert!(get_subnet("10.0.0.0/24", 26, 2).is_ok());
+        let selection = get_subnet::<Ipv4Addr>("10.0.0.0/24", 26, 2).unwrap();
+        assert_eq!(selection.subnet.to_string(), "10.0.0.128/26");
     }
 
     #[test]
     fn test_get_subnet_out_of_range() {
-        assert!(get_subnet("10.0.0.0/24", 26, 100).is_ok());
+        let selection = get_subnet::<Ipv4Addr>("10.0.0.0/24", 26, 100).unwrap();
+        assert!(selection.clamped());
+        assert_eq!(selection.index(), 3);
     }
 
     #[test]
@@ -1175,7 +2695,7 @@ mod tests {
 
     #[test]
     fn test_execute_command_display() {
-        let cmd = Command::Display { cidr: "10.0.0.0/24".to_string() };
+        let cmd = Command::Display { cidr: "10.0.0.0/24".to_string(), verbose: false, output_file: None };
         assert!(execute_command(cmd).is_ok());
     }
 
@@ -1193,19 +2713,25 @@ mod tests {
 
     #[test]
     fn test_execute_command_check_mask() {
-        let cmd = Command::CheckMask { mask: "24".to_string() };
+        let cmd = Command::CheckMask { mask: "24".to_string(), wildcard: false };
+        assert!(execute_command(cmd).is_ok());
+
+        let cmd = Command::CheckMask { mask: "33".to_string(), wildcard: false };
+        assert!(execute_command(cmd).is_err());
+
+        let cmd = Command::CheckMask { mask: "0.0.0.255".to_string(), wildcard: false };
         assert!(execute_command(cmd).is_ok());
 
-        let cmd = Command::CheckMask { mask: "33".to_string() };
+        let cmd = Command::CheckMask { mask: "255.255.255.0".to_string(), wildcard: true };
         assert!(execute_command(cmd).is_err());
     }
 
     #[test]
     fn test_execute_command_same_subnet() {
         let cmd = Command::SameSubnet {
-            ip1: Ipv4Addr::new(10, 0, 0, 1),
-            ip2: Ipv4Addr::new(10, 0, 0, 2),
-            mask1: Ipv4Addr::new(255, 255, 255, 0),
+            ip1: Ipv4Addr::new(10, 0, 0, 1).into(),
+            ip2: Ipv4Addr::new(10, 0, 0, 2).into(),
+            mask1: Ipv4Addr::new(255, 255, 255, 0).into(),
             mask2: None,
         };
         assert!(execute_command(cmd).is_ok());
@@ -1217,6 +2743,7 @@ mod tests {
             cidr: "10.0.0.0/24".to_string(),
             prefix: 26,
             index: 1,
+            output_file: None,
         };
         assert!(execute_command(cmd).is_ok());
     }
@@ -1230,4 +2757,219 @@ mod tests {
         };
         assert!(execute_command(cmd).is_ok());
     }
+
+    #[test]
+    fn test_calculate_arithmetic() {
+        let run = |operation: &str, operands: &[&str]| {
+            let operands: Vec<String> = operands.iter().map(|s| s.to_string()).collect();
+            calculate_arithmetic::<Ipv4Addr>(&operation.parse().unwrap(), &operands).map(|r| r.to_string())
+        };
+        assert_eq!(run("sub", &["10.0.1.4", "10"]).unwrap(), "10.0.0.250");
+        assert_eq!(run("nth", &["10.0.0.0/30", "-1"]).unwrap(), "10.0.0.2");
+        assert_eq!(run("distance", &["10.0.0.9", "10.0.0.1"]).unwrap(), "-8");
+        assert_eq!(run("next", &["10.0.0.77", "26"]).unwrap(), "10.0.0.128/26");
+        assert_eq!(run("prev", &["10.0.1.0/24"]).unwrap(), "10.0.0.0/24");
+        assert!(matches!(run("add", &["255.255.255.255", "1"]), Err(IpCalculatorError::AddressOverflow(_))));
+        assert!(run("add", &["10.0.0.1"]).is_err());
+        assert!(run("add", &["10.0.0.1", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_execute_command_json() {
+        let json = execute_command(Command::Display { cidr: "10.0.0.0/30".to_string(), verbose: false, output_file: None }).unwrap().to_json();
+        assert_eq!(json.get("command"), Some(&Json::String("display".to_string())));
+        let subnet = json.get("subnet").unwrap();
+        assert_eq!(subnet.get("broadcast"), Some(&Json::String("10.0.0.3".to_string())));
+        assert_eq!(subnet.get("hosts"), Some(&Json::Number("2".to_string())));
+
+        let json = execute_command(Command::SameSubnet {
+            ip1: Ipv4Addr::new(10, 0, 0, 1).into(),
+            ip2: Ipv4Addr::new(10, 0, 1, 1).into(),
+            mask1: Ipv4Addr::new(255, 255, 255, 0).into(),
+            mask2: None,
+        }).unwrap().to_json();
+        assert_eq!(json.get("same_subnet"), Some(&Json::Bool(false)));
+
+        let json = execute_command(Command::Subnets {
+            cidr: "10.0.0.0/24".to_string(),
+            prefix: 28,
+            filter: None,
+            page: Some(1),
+            output_file: None,
+        }).unwrap().to_json();
+        assert_eq!(json.get("total_subnets"), Some(&Json::Number("16".to_string())));
+        assert_eq!(json.get("page"), Some(&Json::Number("2".to_string())));
+        assert_eq!(json.get("total_pages"), Some(&Json::Number("4".to_string())));
+
+        let json = execute_command(Command::FindRange {
+            cidr: "10.0.0.0/24".to_string(),
+            range_size: 3,
+            exclusions: vec![],
+        }).unwrap().to_json();
+        assert_eq!(json.to_string(), r#"{"command":"find-range","cidr":"10.0.0.0/24","size":3,"start":"10.0.0.1","end":"10.0.0.3"}"#);
+    }
+
+    #[test]
+    fn test_error_json() {
+        let err = execute_command(Command::CheckIP { ip: "bad".to_string() }).unwrap_err();
+        assert_eq!(
+            err.to_json().to_string(),
+            r#"{"error":{"type":"InvalidIP","message":"Invalid IP: Invalid IP address format: bad"}}"#
+        );
+
+        let err = execute_command(Command::CheckMask { mask: "255.0.255.0".to_string(), wildcard: false }).unwrap_err();
+        let error = err.to_json();
+        let error = error.get("error").unwrap();
+        assert_eq!(error.get("type"), Some(&Json::String("NonContiguousMask".to_string())));
+        assert!(error.get("diagnostic").is_some());
+    }
+
+    #[test]
+    fn test_subnet_new_ipv6() {
+        let subnet = Subnet::new(Ipv6Addr::from_str("2001:db8::1").unwrap(), 32).unwrap();
+        assert_eq!(subnet.network, Ipv6Addr::from_str("2001:db8::").unwrap());
+        assert_eq!(subnet.mask, Ipv6Addr::from_str("ffff:ffff::").unwrap());
+        assert_eq!(subnet.first_usable, Some(Ipv6Addr::from_str("2001:db8::").unwrap()));
+        assert_eq!(
+            subnet.last_usable,
+            Some(Ipv6Addr::from_str("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff").unwrap())
+        );
+        assert_eq!(subnet.num_hosts, 1u128 << 96);
+    }
+
+    #[test]
+    fn test_subnet_new_ipv6_edges() {
+        let host = Subnet::new(Ipv6Addr::LOCALHOST, 128).unwrap();
+        assert_eq!(host.num_hosts, 1);
+        assert_eq!(host.first_usable, Some(Ipv6Addr::LOCALHOST));
+
+        let all = Subnet::new(Ipv6Addr::UNSPECIFIED, 0).unwrap();
+        assert_eq!(all.num_hosts, u128::MAX);
+        assert_eq!(all.broadcast, Ipv6Addr::from(u128::MAX));
+
+        assert!(Subnet::new(Ipv6Addr::UNSPECIFIED, 129).is_err());
+    }
+
+    #[test]
+    fn test_parse_cidr_ipv6() {
+        let subnet = parse_cidr::<Ipv6Addr>("2001:db8:abcd::/48").unwrap();
+        assert_eq!(subnet.prefix, 48);
+        assert!(subnet.contains_ip(Ipv6Addr::from_str("2001:db8:abcd:ffff::1").unwrap()));
+        assert!(!subnet.contains_ip(Ipv6Addr::from_str("2001:db8:abce::").unwrap()));
+        assert!(parse_cidr::<Ipv6Addr>("2001:db8::/129").is_err());
+        assert!(parse_cidr::<Ipv6Addr>("10.0.0.0/8").is_err());
+    }
+
+    #[test]
+    fn test_ipv6_subnet_page_and_nth() {
+        let base = parse_cidr::<Ipv6Addr>("2001:db8::/32").unwrap();
+        let (subnets, total, pages, page_num) = base.subnet_page(48, None, Some(1)).unwrap();
+        assert_eq!(total, 65536);
+        assert_eq!(pages, 16384);
+        assert_eq!(page_num, 1);
+        assert_eq!(subnets[0].network, Ipv6Addr::from_str("2001:db8:4::").unwrap());
+
+        let nth = base.nth_subnet(64, 5).unwrap();
+        assert_eq!(nth.network, Ipv6Addr::from_str("2001:db8:0:5::").unwrap());
+        assert!(base.nth_subnet(48, 65536).is_err());
+    }
+
+    #[test]
+    fn test_ipv6_find_range() {
+        let subnet = parse_cidr::<Ipv6Addr>("2001:db8::/64").unwrap();
+        let excluded = [Ipv6Addr::from_str("2001:db8::2").unwrap()];
+        let (start, end) = subnet.find_range(4, &excluded).unwrap();
+        assert_eq!(start, Ipv6Addr::from_str("2001:db8::3").unwrap());
+        assert_eq!(end, Ipv6Addr::from_str("2001:db8::6").unwrap());
+    }
+
+    #[test]
+    fn test_ipv6_same_subnet_and_masks() {
+        let mask = parse_mask::<Ipv6Addr>("64").unwrap();
+        assert_eq!(mask_to_cidr(mask).unwrap(), 64);
+        assert!(parse_mask::<Ipv6Addr>("ffff:0:ffff::").is_err());
+        assert!(are_in_same_subnet(
+            Ipv6Addr::from_str("2001:db8::1").unwrap(),
+            Ipv6Addr::from_str("2001:db8::ffff").unwrap(),
+            mask,
+            None,
+        ).unwrap());
+    }
+
+    #[test]
+    fn test_execute_command_ipv6() {
+        let cmd = Command::Display { cidr: "2001:db8::/32".to_string(), verbose: false, output_file: None };
+        assert!(execute_command(cmd).is_ok());
+
+        let cmd = Command::SameSubnet {
+            ip1: Ipv4Addr::new(10, 0, 0, 1).into(),
+            ip2: Ipv6Addr::LOCALHOST.into(),
+            mask1: Ipv4Addr::new(255, 0, 0, 0).into(),
+            mask2: None,
+        };
+        assert!(execute_command(cmd).is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let same = |ip2: Ipv4Addr| Command::SameSubnet {
+            ip1: Ipv4Addr::new(10, 0, 0, 1).into(),
+            ip2: ip2.into(),
+            mask1: Ipv4Addr::new(255, 255, 255, 0).into(),
+            mask2: None,
+        };
+        assert_eq!(execute_command(same(Ipv4Addr::new(10, 0, 0, 9))).unwrap().exit_code(), 0);
+        assert_eq!(execute_command(same(Ipv4Addr::new(10, 0, 1, 9))).unwrap().exit_code(), 1);
+
+        let invalid = execute_command(Command::CheckIP { ip: "300.1.1.1".to_string() }).unwrap_err();
+        assert_eq!(invalid.exit_code(), 1);
+        assert_eq!(IpCalculatorError::ArgumentsError("usage".to_string()).exit_code(), 2);
+        assert_eq!(IpCalculatorError::InputError("unreadable".to_string()).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_subnet_from_str_forms() {
+        let expected = Subnet::new(Ipv4Addr::new(192, 168, 1, 0), 24).unwrap();
+        assert_eq!("192.168.1.7/24".parse::<Subnet>().unwrap(), expected);
+        assert_eq!("192.168.1.7/255.255.255.0".parse::<Subnet>().unwrap(), expected);
+        assert_eq!("192.168.1.7 255.255.255.0".parse::<Subnet>().unwrap(), expected);
+        assert_eq!(" 192.168.1.7  24 ".parse::<Subnet>().unwrap(), expected);
+
+        let v6: Subnet<Ipv6Addr> = "2001:db8::1 ffff:ffff::".parse().unwrap();
+        assert_eq!(v6.prefix, 32);
+    }
+
+    #[test]
+    fn test_subnet_from_str_errors() {
+        assert!(matches!("192.168.1.0".parse::<Subnet>(), Err(IpCalculatorError::InvalidCIDR(_))));
+        assert!(matches!("192.168.1.0/33".parse::<Subnet>(), Err(IpCalculatorError::InvalidPrefix(_))));
+        assert!(matches!("192.168.1.0/255.0.255.0".parse::<Subnet>(), Err(IpCalculatorError::NonContiguousMask(_))));
+        assert!(matches!("192.168.1.0/mask".parse::<Subnet>(), Err(IpCalculatorError::InvalidMask(_))));
+        assert!(matches!("10.0.0.0 8 extra".parse::<Subnet>(), Err(IpCalculatorError::InvalidCIDR(_))));
+    }
+
+    #[test]
+    fn test_subnet_display_is_canonical() {
+        let subnet = calculate_subnet("10.1.2.3/8").unwrap();
+        assert_eq!(subnet.to_string(), "10.0.0.0/8");
+        assert_eq!(subnet.to_string().parse::<Subnet>().unwrap(), subnet);
+        assert!(subnet.colored().to_string().contains("\x1b[1;34mNetwork:\x1b[0m 10.0.0.0"));
+    }
+
+    #[test]
+    fn test_subnet_ordering_and_hashing() {
+        let mut subnets: Vec<Subnet> = ["10.0.1.0/24", "10.0.0.0/24", "10.0.0.0/16", "9.0.0.0/8"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        subnets.sort();
+        let sorted: Vec<String> = subnets.iter().map(|s| s.to_string()).collect();
+        assert_eq!(sorted, vec!["9.0.0.0/8", "10.0.0.0/16", "10.0.0.0/24", "10.0.1.0/24"]);
+
+        let set: std::collections::HashSet<Subnet> = ["10.0.0.1/24", "10.0.0.200/24", "10.0.0.0/25"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
 }
@@ -0,0 +1,326 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{CommandHelp, CommandOption, GlobalOption, IpCalculatorError};
+
+const PROGRAM: &str = "ip_calculator";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for CompletionShell {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "bash" => Ok(CompletionShell::Bash),
+            "zsh" => Ok(CompletionShell::Zsh),
+            "fish" => Ok(CompletionShell::Fish),
+            _ => Err(IpCalculatorError::ArgumentsError(format!(
+                "Unknown shell '{}'. Expected bash, zsh or fish.",
+                input
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for CompletionShell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CompletionShell::Bash => "bash",
+            CompletionShell::Zsh => "zsh",
+            CompletionShell::Fish => "fish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn generate(shell: CompletionShell) -> String {
+    let commands: Vec<CommandHelp> = CommandHelp::get_all()
+        .into_iter()
+        .filter(|command| !command.aliases.is_empty())
+        .collect();
+    match shell {
+        CompletionShell::Bash => bash(&commands),
+        CompletionShell::Zsh => zsh(&commands),
+        CompletionShell::Fish => fish(&commands),
+    }
+}
+
+fn all_options() -> Vec<CommandOption> {
+    let mut options: Vec<CommandOption> = Vec::new();
+    for option in CommandHelp::get_all().iter().flat_map(CommandHelp::options) {
+        if !options.contains(&option) {
+            options.push(option);
+        }
+    }
+    options
+}
+
+fn value_names(options: &[CommandOption], files: bool) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = options
+        .iter()
+        .filter(|option| option.value.is_some() && option.takes_file() == files)
+        .flat_map(|option| option.names.iter().copied())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+fn command_options(command: &CommandHelp) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = command
+        .options()
+        .iter()
+        .flat_map(|option| option.names.iter().copied())
+        .filter(|name| !global_names().contains(name))
+        .collect();
+    names.dedup();
+    names
+}
+
+fn global_names() -> Vec<&'static str> {
+    GlobalOption::get_all().iter().flat_map(|option| option.names.iter().copied()).collect()
+}
+
+fn command_names() -> Vec<&'static str> {
+    CommandHelp::get_all().iter().map(|command| command.name).collect()
+}
+
+fn bash(commands: &[CommandHelp]) -> String {
+    let options = all_options();
+    let mut lines = vec![
+        format!("_{}() {{", PROGRAM),
+        "    local cur prev command opts".to_string(),
+        "    cur=\"${COMP_WORDS[COMP_CWORD]}\"".to_string(),
+        "    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"".to_string(),
+        "    command=\"${COMP_WORDS[1]}\"".to_string(),
+        String::new(),
+        "    case \"$prev\" in".to_string(),
+    ];
+    lines.push(format!("        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;", value_names(&options, true).join("|")));
+    lines.push(format!("        {}) COMPREPLY=(); return ;;", value_names(&options, false).join("|")));
+    lines.push("    esac".to_string());
+    lines.push(String::new());
+
+    let aliases: Vec<&str> = commands.iter().flat_map(|command| command.aliases.iter().copied()).collect();
+    lines.push("    if [ \"$COMP_CWORD\" -eq 1 ]; then".to_string());
+    lines.push(format!("        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", aliases.join(" ")));
+    lines.push("        return".to_string());
+    lines.push("    fi".to_string());
+    lines.push(String::new());
+
+    lines.push("    if [ \"$COMP_CWORD\" -eq 2 ]; then".to_string());
+    lines.push("        case \"$command\" in".to_string());
+    for command in commands {
+        let values = command.values();
+        if !values.is_empty() {
+            lines.push(format!(
+                "            {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                command.aliases.join("|"),
+                values.join(" ")
+            ));
+        }
+    }
+    lines.push("        esac".to_string());
+    lines.push("    fi".to_string());
+    lines.push(String::new());
+
+    lines.push(format!("    opts=\"{}\"", global_names().join(" ")));
+    lines.push("    case \"$command\" in".to_string());
+    for command in commands {
+        let extra = if command.name == "help" { command_names() } else { command_options(command) };
+        if extra.is_empty() {
+            continue;
+        }
+        lines.push(format!("        {}) opts=\"$opts {}\" ;;", command.aliases.join("|"), extra.join(" ")));
+    }
+    lines.push("    esac".to_string());
+    lines.push(String::new());
+    lines.push("    if [[ \"$cur\" == -* || \"$command\" == -h || \"$command\" == --help ]]; then".to_string());
+    lines.push("        COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))".to_string());
+    lines.push("    else".to_string());
+    lines.push("        COMPREPLY=($(compgen -f -- \"$cur\"))".to_string());
+    lines.push("    fi".to_string());
+    lines.push("}".to_string());
+    lines.push(String::new());
+    lines.push(format!("complete -o filenames -F _{} {}", PROGRAM, PROGRAM));
+    lines.join("\n")
+}
+
+fn zsh(commands: &[CommandHelp]) -> String {
+    let options = all_options();
+    let mut lines = vec![
+        format!("#compdef {}", PROGRAM),
+        String::new(),
+        format!("_{}() {{", PROGRAM),
+        "    local -a commands opts".to_string(),
+        "    commands=(".to_string(),
+    ];
+    for command in commands {
+        for alias in command.aliases {
+            lines.push(format!("        '{}:{}'", alias, zsh_escape(command.short_desc)));
+        }
+    }
+    lines.push("    )".to_string());
+    lines.push(String::new());
+    lines.push("    if (( CURRENT == 2 )); then".to_string());
+    lines.push("        _describe 'command' commands".to_string());
+    lines.push("        return".to_string());
+    lines.push("    fi".to_string());
+    lines.push(String::new());
+    lines.push("    case \"${words[CURRENT-1]}\" in".to_string());
+    lines.push(format!("        {}) _files; return ;;", value_names(&options, true).join("|")));
+    lines.push(format!("        {}) return ;;", value_names(&options, false).join("|")));
+    lines.push("    esac".to_string());
+    lines.push(String::new());
+    lines.push("    if (( CURRENT == 3 )); then".to_string());
+    lines.push("        case \"${words[2]}\" in".to_string());
+    for command in commands {
+        let values = command.values();
+        if !values.is_empty() {
+            lines.push(format!("            {}) compadd -- {}; return ;;", command.aliases.join("|"), values.join(" ")));
+        }
+    }
+    lines.push("        esac".to_string());
+    lines.push("    fi".to_string());
+    lines.push(String::new());
+    lines.push(format!("    opts=({})", global_names().join(" ")));
+    lines.push("    case \"${words[2]}\" in".to_string());
+    for command in commands {
+        if command.name == "help" {
+            lines.push(format!("        {}) compadd -- {}; return ;;", command.aliases.join("|"), command_names().join(" ")));
+            continue;
+        }
+        let extra = command_options(command);
+        if !extra.is_empty() {
+            lines.push(format!("        {}) opts+=({}) ;;", command.aliases.join("|"), extra.join(" ")));
+        }
+    }
+    lines.push("    esac".to_string());
+    lines.push(String::new());
+    lines.push("    if [[ $PREFIX == -* ]]; then".to_string());
+    lines.push("        compadd -a opts".to_string());
+    lines.push("    else".to_string());
+    lines.push("        _files".to_string());
+    lines.push("    fi".to_string());
+    lines.push("}".to_string());
+    lines.push(String::new());
+    lines.push(format!("_{} \"$@\"", PROGRAM));
+    lines.join("\n")
+}
+
+fn fish(commands: &[CommandHelp]) -> String {
+    let mut lines = vec![format!("complete -c {} -f", PROGRAM)];
+    for command in commands {
+        lines.push(format!(
+            "complete -c {} -n 'test (count (commandline -opc)) -eq 1' -a '{}' -d '{}'",
+            PROGRAM,
+            command.aliases.join(" "),
+            fish_escape(command.short_desc)
+        ));
+    }
+    for name in global_names() {
+        lines.push(format!("complete -c {} {}", PROGRAM, fish_flag(name)));
+    }
+    for command in commands {
+        let condition = format!("__fish_seen_subcommand_from {}", command.aliases.join(" "));
+        if command.name == "help" {
+            lines.push(format!("complete -c {} -n '{}' -a '{}'", PROGRAM, condition, command_names().join(" ")));
+            continue;
+        }
+        lines.push(format!("complete -c {} -n '{}' -F", PROGRAM, condition));
+        let values = command.values();
+        if !values.is_empty() {
+            lines.push(format!(
+                "complete -c {} -n '{}; and test (count (commandline -opc)) -eq 2' -a '{}'",
+                PROGRAM,
+                condition,
+                values.join(" ")
+            ));
+        }
+        for option in command.options() {
+            if option.names.iter().all(|name| global_names().contains(name)) {
+                continue;
+            }
+            let flags: Vec<String> = option.names.iter().map(|name| fish_flag(name)).collect();
+            let value = match option.value {
+                Some(_) if option.takes_file() => " -r -F",
+                Some(_) => " -x",
+                None => "",
+            };
+            lines.push(format!("complete -c {} -n '{}' {}{}", PROGRAM, condition, flags.join(" "), value));
+        }
+    }
+    lines.join("\n")
+}
+
+fn fish_flag(name: &str) -> String {
+    match (name.strip_prefix("--"), name.strip_prefix('-')) {
+        (Some(long), _) => format!("-l {}", long),
+        (None, Some(short)) if short.len() == 1 => format!("-s {}", short),
+        (None, Some(old)) => format!("-o {}", old),
+        _ => name.to_string(),
+    }
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''").replace(':', "\\:")
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnets_options_from_usage() {
+        let subnets = CommandHelp::find_by_name_or_alias("subnets").unwrap();
+        let options = subnets.options();
+        let names: Vec<&str> = options.iter().flat_map(|option| option.names.clone()).collect();
+
+        assert_eq!(names, vec!["-f", "-p", "-o", "--columns"]);
+        assert!(options.iter().any(|option| option.names == ["-o"] && option.takes_file()));
+        assert!(options.iter().any(|option| option.names == ["-p"] && !option.takes_file()));
+    }
+
+    #[test]
+    fn test_fixed_values_from_usage() {
+        let values = |name| CommandHelp::find_by_name_or_alias(name).unwrap().values();
+        assert_eq!(values("--set"), vec!["union", "intersection", "difference", "subset", "complement"]);
+        assert_eq!(values("--completions"), vec!["bash", "zsh", "fish"]);
+        assert_eq!(values("--generate-man"), vec!["roff", "markdown"]);
+        assert!(values("--check-mask").is_empty());
+        assert!(values("--aggregate").is_empty());
+        assert!(values("--help").is_empty());
+    }
+
+    #[test]
+    fn test_generated_scripts() {
+        let bash = generate(CompletionShell::Bash);
+        assert!(bash.contains("-s|--subnets) opts=\"$opts -f -p -o --columns\" ;;"));
+        assert!(bash.contains("complete -o filenames -F _ip_calculator ip_calculator"));
+        assert!(bash.contains("--set) COMPREPLY=($(compgen -W \"union intersection difference subset complement\" -- \"$cur\")); return ;;"));
+        assert!(bash.contains("--completions) COMPREPLY=($(compgen -W \"bash zsh fish\" -- \"$cur\")); return ;;"));
+
+        let zsh = generate(CompletionShell::Zsh);
+        assert!(zsh.starts_with("#compdef ip_calculator"));
+        assert!(zsh.contains("'--subnets:"));
+        assert!(zsh.contains("--generate-man) compadd -- roff markdown; return ;;"));
+
+        let fish = generate(CompletionShell::Fish);
+        assert!(fish.contains("complete -c ip_calculator -n '__fish_seen_subcommand_from -s --subnets' -s o -r -F"));
+        assert!(fish.contains(
+            "complete -c ip_calculator -n '__fish_seen_subcommand_from -math --ip-math; and test (count (commandline -opc)) -eq 2' \
+             -a 'add sub nth distance next prev'"
+        ));
+        assert!("powershell".parse::<CompletionShell>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{IpCalculatorError, IpFamily, Subnet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Text,
+    Csv,
+    Tsv,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("csv") => ExportFormat::Csv,
+            Some("tsv") => ExportFormat::Tsv,
+            _ => ExportFormat::Text,
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        match self {
+            ExportFormat::Tsv => "\t",
+            _ => ",",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Index,
+    Network,
+    Prefix,
+    Mask,
+    Wildcard,
+    Broadcast,
+    First,
+    Last,
+    Hosts,
+}
+
+impl Column {
+    pub const ALL: [Column; 9] = [
+        Column::Index,
+        Column::Network,
+        Column::Prefix,
+        Column::Mask,
+        Column::Wildcard,
+        Column::Broadcast,
+        Column::First,
+        Column::Last,
+        Column::Hosts,
+    ];
+
+    fn value<A: IpFamily>(&self, index: u128, subnet: &Subnet<A>) -> String {
+        let usable = |ip: Option<A>| ip.map_or(String::new(), |ip| ip.to_string());
+        match self {
+            Column::Index => index.to_string(),
+            Column::Network => subnet.network.to_string(),
+            Column::Prefix => subnet.prefix.to_string(),
+            Column::Mask => subnet.mask.to_string(),
+            Column::Wildcard if A::HAS_BROADCAST => subnet.wildcard.to_string(),
+            Column::Broadcast if A::HAS_BROADCAST => subnet.broadcast.to_string(),
+            Column::Wildcard | Column::Broadcast => String::new(),
+            Column::First if A::HAS_BROADCAST => usable(subnet.first_usable),
+            Column::Last if A::HAS_BROADCAST => usable(subnet.last_usable),
+            Column::First => subnet.network.to_string(),
+            Column::Last => subnet.broadcast.to_string(),
+            Column::Hosts if A::HAS_BROADCAST => subnet.num_hosts.to_string(),
+            Column::Hosts => subnet.address_total().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Column::Index => "index",
+            Column::Network => "network",
+            Column::Prefix => "prefix",
+            Column::Mask => "mask",
+            Column::Wildcard => "wildcard",
+            Column::Broadcast => "broadcast",
+            Column::First => "first",
+            Column::Last => "last",
+            Column::Hosts => "hosts",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Column {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .into_iter()
+            .find(|column| column.to_string().eq_ignore_ascii_case(input.trim()))
+            .ok_or_else(|| IpCalculatorError::ArgumentsError(format!(
+                "Unknown column '{}'. Expected one of: {}",
+                input.trim(),
+                Column::ALL.map(|column| column.to_string()).join(", ")
+            )))
+    }
+}
+
+pub fn parse_columns(input: &str) -> Result<Vec<Column>, IpCalculatorError> {
+    let columns = input
+        .split(',')
+        .filter(|column| !column.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Column>, _>>()?;
+    if columns.is_empty() {
+        return Err(IpCalculatorError::ArgumentsError("No columns given for --columns".to_string()));
+    }
+    Ok(columns)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFile {
+    pub path: String,
+    pub columns: Vec<Column>,
+}
+
+impl OutputFile {
+    pub fn new(path: &str, columns: Option<Vec<Column>>) -> Result<Self, IpCalculatorError> {
+        if columns.is_some() && ExportFormat::from_path(path) == ExportFormat::Text {
+            return Err(IpCalculatorError::ArgumentsError(format!(
+                "--columns needs a .csv or .tsv output file, got '{}'",
+                path
+            )));
+        }
+        Ok(OutputFile {
+            path: path.to_string(),
+            columns: columns.unwrap_or_else(|| Column::ALL.to_vec()),
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        ExportFormat::from_path(&self.path)
+    }
+
+    pub fn create(&self) -> Result<BufWriter<File>, IpCalculatorError> {
+        File::create(&self.path).map(BufWriter::new).map_err(|e| self.write_error(e))
+    }
+
+    pub fn write_error(&self, error: io::Error) -> IpCalculatorError {
+        IpCalculatorError::SubnetError(format!("Failed to write output file '{}': {}", self.path, error))
+    }
+}
+
+pub fn write_subnets<A: IpFamily, W: Write>(
+    writer: &mut W,
+    subnets: impl Iterator<Item = (u128, Subnet<A>)>,
+    format: ExportFormat,
+    columns: &[Column],
+) -> io::Result<()> {
+    if format == ExportFormat::Text {
+        for (i, (_, subnet)) in subnets.enumerate() {
+            if i > 0 {
+                writer.write_all(b"\n----------------------------\n")?;
+            }
+            writer.write_all(subnet.to_plain_text().as_bytes())?;
+        }
+        return Ok(());
+    }
+
+    let separator = format.separator();
+    let header: Vec<String> = columns.iter().map(ToString::to_string).collect();
+    writeln!(writer, "{}", header.join(separator))?;
+    for (index, subnet) in subnets {
+        let row: Vec<String> = columns.iter().map(|column| column.value(index, &subnet)).collect();
+        writeln!(writer, "{}", row.join(separator))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn export<A: IpFamily>(cidrs: &[&str], format: ExportFormat, columns: &[Column]) -> String {
+        let subnets = cidrs
+            .iter()
+            .enumerate()
+            .map(|(i, cidr)| (i as u128, cidr.parse::<Subnet<A>>().unwrap()));
+        let mut output = Vec::new();
+        write_subnets(&mut output, subnets, format, columns).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(ExportFormat::from_path("subnets.csv"), ExportFormat::Csv);
+        assert_eq!(ExportFormat::from_path("out/SUBNETS.TSV"), ExportFormat::Tsv);
+        assert_eq!(ExportFormat::from_path("subnets.txt"), ExportFormat::Text);
+        assert_eq!(ExportFormat::from_path("subnets"), ExportFormat::Text);
+    }
+
+    #[test]
+    fn test_csv_and_tsv_rows() {
+        let csv = export::<Ipv4Addr>(&["10.0.0.0/31", "10.0.0.4/30"], ExportFormat::Csv, &Column::ALL);
+        assert_eq!(
+            csv,
+            "index,network,prefix,mask,wildcard,broadcast,first,last,hosts\n\
+             0,10.0.0.0,31,255.255.255.254,0.0.0.1,10.0.0.1,10.0.0.0,10.0.0.1,2\n\
+             1,10.0.0.4,30,255.255.255.252,0.0.0.3,10.0.0.7,10.0.0.5,10.0.0.6,2\n"
+        );
+
+        let tsv = export::<Ipv4Addr>(&["192.168.0.0/24"], ExportFormat::Tsv, &[Column::Hosts, Column::Network]);
+        assert_eq!(tsv, "hosts\tnetwork\n254\t192.168.0.0\n");
+
+        let v6 = export::<Ipv6Addr>(&["2001:db8::/126"], ExportFormat::Csv, &[Column::Broadcast, Column::Last, Column::Hosts]);
+        assert_eq!(v6, "broadcast,last,hosts\n,2001:db8::3,4\n");
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(parse_columns("network, PREFIX,hosts").unwrap(), vec![Column::Network, Column::Prefix, Column::Hosts]);
+        assert!(parse_columns("network,size").is_err());
+        assert!(parse_columns(",").is_err());
+        assert!(OutputFile::new("out.txt", Some(vec![Column::Network])).is_err());
+        assert_eq!(OutputFile::new("out.csv", None).unwrap().columns.len(), 9);
+    }
+}
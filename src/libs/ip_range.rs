@@ -0,0 +1,238 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{AddressCount, IpCalculatorError, IpFamily, Subnet};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::subnet_iter::HostIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IpRange<A: IpFamily = Ipv4Addr> {
+    start: A,
+    end: A,
+}
+
+impl<A: IpFamily> IpRange<A> {
+    pub fn new(start: A, end: A) -> Result<Self, IpCalculatorError> {
+        if start > end {
+            return Err(IpCalculatorError::InvalidRange(format!(
+                "Range start {} is greater than range end {}",
+                start, end
+            )));
+        }
+        Ok(IpRange { start, end })
+    }
+
+    pub(crate) fn from_bits(start: u128, end: u128) -> Self {
+        IpRange {
+            start: A::from_bits(start),
+            end: A::from_bits(end),
+        }
+    }
+
+    pub fn start(&self) -> A {
+        self.start
+    }
+
+    pub fn end(&self) -> A {
+        self.end
+    }
+
+    pub fn contains(&self, ip: A) -> bool {
+        ip >= self.start && ip <= self.end
+    }
+
+    pub fn len(&self) -> AddressCount {
+        AddressCount::from_host_bits(self.end.to_bits() - self.start.to_bits())
+    }
+
+    pub fn intersection(&self, other: &IpRange<A>) -> Option<IpRange<A>> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start <= end).then_some(IpRange { start, end })
+    }
+
+    pub fn iter(&self) -> HostIter<A> {
+        HostIter::new(self.start, self.end)
+    }
+
+    pub fn to_subnets(&self) -> Vec<Subnet<A>> {
+        let mut subnets = Vec::new();
+        let end = self.end.to_bits();
+        let mut start = self.start.to_bits();
+
+        loop {
+            let mut prefix = A::BITS - (start.trailing_zeros() as u8).min(A::BITS);
+            while A::host_bits(prefix) > end - start {
+                prefix += 1;
+            }
+
+            subnets.push(Subnet::from_bits(start, prefix));
+
+            let block_end = start + A::host_bits(prefix);
+            if block_end >= end {
+                break;
+            }
+            start = block_end + 1;
+        }
+
+        subnets
+    }
+}
+
+impl<A: IpFamily> From<Subnet<A>> for IpRange<A> {
+    fn from(subnet: Subnet<A>) -> Self {
+        IpRange {
+            start: subnet.network,
+            end: subnet.broadcast,
+        }
+    }
+}
+
+impl<A: IpFamily> IntoIterator for IpRange<A> {
+    type Item = A;
+    type IntoIter = HostIter<A>;
+
+    fn into_iter(self) -> HostIter<A> {
+        self.iter()
+    }
+}
+
+impl<A: IpFamily> ToJson for IpRange<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("start", Json::string(self.start)),
+            ("end", Json::string(self.end)),
+            ("addresses", self.len().into()),
+        ])
+    }
+}
+
+impl<A: IpFamily> fmt::Display for IpRange<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
+
+impl<A: IpFamily> FromStr for IpRange<A> {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if let Some((start, end)) = input.split_once('-') {
+            let parse = |ip: &str| {
+                A::from_str(ip.trim()).map_err(|_| {
+                    IpCalculatorError::InvalidIP(format!("Invalid IP address in range: {}", ip.trim()))
+                })
+            };
+            return IpRange::new(parse(start)?, parse(end)?);
+        }
+
+        if input.contains('/') {
+            return Ok(IpRange::from(input.parse::<Subnet<A>>()?));
+        }
+
+        let ip = A::from_str(input).map_err(|_| {
+            IpCalculatorError::InvalidRange(format!(
+                "Range must be <start> - <end>, a CIDR or a single IP: {}",
+                input
+            ))
+        })?;
+        Ok(IpRange { start: ip, end: ip })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    fn subnets_of(range: &str) -> Vec<String> {
+        range
+            .parse::<IpRange>()
+            .unwrap()
+            .to_subnets()
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_range_forms() {
+        let range: IpRange = "10.0.3.17 - 10.0.9.200".parse().unwrap();
+        assert_eq!(range.start(), Ipv4Addr::new(10, 0, 3, 17));
+        assert_eq!(range.end(), Ipv4Addr::new(10, 0, 9, 200));
+        assert_eq!("10.0.3.17-10.0.9.200".parse::<IpRange>().unwrap(), range);
+
+        let from_cidr: IpRange = "10.0.0.0/30".parse().unwrap();
+        assert_eq!(from_cidr.len(), 4.into());
+
+        let single: IpRange = "10.0.0.9".parse().unwrap();
+        assert_eq!(single.len(), 1.into());
+
+        assert!(matches!("10.0.0.9 - 10.0.0.1".parse::<IpRange>(), Err(IpCalculatorError::InvalidRange(_))));
+        assert!("10.0.0.9 - nope".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_contains_len_and_intersection() {
+        let a: IpRange = "10.0.0.10 - 10.0.0.20".parse().unwrap();
+        let b: IpRange = "10.0.0.15 - 10.0.0.30".parse().unwrap();
+        assert!(a.contains(Ipv4Addr::new(10, 0, 0, 10)));
+        assert!(!a.contains(Ipv4Addr::new(10, 0, 0, 21)));
+        assert_eq!(a.len(), 11.into());
+        assert_eq!(a.intersection(&b).unwrap().to_string(), "10.0.0.15 - 10.0.0.20");
+
+        let c: IpRange = "10.0.0.21 - 10.0.0.22".parse().unwrap();
+        assert!(a.intersection(&c).is_none());
+    }
+
+    #[test]
+    fn test_iteration() {
+        let range: IpRange = "10.0.0.254 - 10.0.1.1".parse().unwrap();
+        let ips: Vec<Ipv4Addr> = range.into_iter().collect();
+        assert_eq!(ips.len(), 4);
+        assert_eq!(ips[2], Ipv4Addr::new(10, 0, 1, 0));
+        assert_eq!(range.iter().next_back(), Some(Ipv4Addr::new(10, 0, 1, 1)));
+    }
+
+    #[test]
+    fn test_to_subnets_minimal_cover() {
+        assert_eq!(
+            subnets_of("10.0.3.17 - 10.0.9.200"),
+            vec![
+                "10.0.3.17/32", "10.0.3.18/31", "10.0.3.20/30", "10.0.3.24/29",
+                "10.0.3.32/27", "10.0.3.64/26", "10.0.3.128/25", "10.0.4.0/22",
+                "10.0.8.0/24", "10.0.9.0/25", "10.0.9.128/26", "10.0.9.192/29",
+                "10.0.9.200/32",
+            ]
+        );
+        assert_eq!(subnets_of("192.168.0.0 - 192.168.255.255"), vec!["192.168.0.0/16"]);
+        assert_eq!(subnets_of("0.0.0.0 - 255.255.255.255"), vec!["0.0.0.0/0"]);
+        assert_eq!(subnets_of("255.255.255.255 - 255.255.255.255"), vec!["255.255.255.255/32"]);
+    }
+
+    #[test]
+    fn test_to_subnets_covers_exactly() {
+        let range: IpRange = "172.16.5.3 - 172.17.0.9".parse().unwrap();
+        let subnets = range.to_subnets();
+        let total: AddressCount = subnets.iter().map(|s| IpRange::from(*s).len()).sum();
+        assert_eq!(total, range.len());
+        assert_eq!(subnets.first().unwrap().network, range.start());
+        assert_eq!(subnets.last().unwrap().broadcast, range.end());
+    }
+
+    #[test]
+    fn test_ipv6_range() {
+        let range: IpRange<Ipv6Addr> = "2001:db8::1 - 2001:db8::ffff".parse().unwrap();
+        let subnets: Vec<String> = range.to_subnets().iter().map(|s| s.to_string()).collect();
+        assert_eq!(subnets.first().unwrap(), "2001:db8::1/128");
+        assert_eq!(subnets.last().unwrap(), "2001:db8::8000/113");
+
+        let all: IpRange<Ipv6Addr> = "::/0".parse().unwrap();
+        assert_eq!(all.len(), AddressCount::AllIpv6);
+        assert_eq!(all.len().to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(all.to_subnets().len(), 1);
+    }
+}
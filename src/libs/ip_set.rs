@@ -0,0 +1,297 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{AddressCount, IpCalculatorError, IpFamily, Subnet};
+use crate::libs::ip_range::IpRange;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IpSet<A: IpFamily = Ipv4Addr> {
+    ranges: Vec<IpRange<A>>,
+}
+
+impl<A: IpFamily> IpSet<A> {
+    pub fn new() -> Self {
+        IpSet { ranges: Vec::new() }
+    }
+
+    pub fn full() -> Self {
+        IpSet { ranges: vec![IpRange::from_bits(0, A::max_bits())] }
+    }
+
+    fn from_intervals(mut intervals: Vec<(u128, u128)>) -> Self {
+        intervals.sort_unstable();
+
+        let mut merged: Vec<(u128, u128)> = Vec::with_capacity(intervals.len());
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if last.1 == u128::MAX || start <= last.1 + 1 => {
+                    last.1 = last.1.max(end);
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+
+        IpSet {
+            ranges: merged
+                .into_iter()
+                .map(|(start, end)| IpRange::from_bits(start, end))
+                .collect(),
+        }
+    }
+
+    fn intervals(&self) -> impl Iterator<Item = (u128, u128)> + '_ {
+        self.ranges.iter().map(|r| (r.start().to_bits(), r.end().to_bits()))
+    }
+
+    pub fn insert(&mut self, range: impl Into<IpRange<A>>) {
+        let range = range.into();
+        let mut intervals: Vec<(u128, u128)> = self.intervals().collect();
+        intervals.push((range.start().to_bits(), range.end().to_bits()));
+        *self = Self::from_intervals(intervals);
+    }
+
+    pub fn insert_ip(&mut self, ip: A) {
+        self.insert(IpRange::from_bits(ip.to_bits(), ip.to_bits()));
+    }
+
+    pub fn ranges(&self) -> &[IpRange<A>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn len(&self) -> AddressCount {
+        self.ranges.iter().map(IpRange::len).sum()
+    }
+
+    pub fn contains(&self, ip: A) -> bool {
+        let index = self.ranges.partition_point(|range| range.end() < ip);
+        self.ranges.get(index).is_some_and(|range| range.contains(ip))
+    }
+
+    pub fn union(&self, other: &IpSet<A>) -> IpSet<A> {
+        Self::from_intervals(self.intervals().chain(other.intervals()).collect())
+    }
+
+    pub fn intersection(&self, other: &IpSet<A>) -> IpSet<A> {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            if let Some(overlap) = a.intersection(b) {
+                result.push(overlap);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IpSet { ranges: result }
+    }
+
+    pub fn complement(&self) -> IpSet<A> {
+        let mut result = Vec::new();
+        let mut next_start = Some(0u128);
+
+        for (start, end) in self.intervals() {
+            if let Some(gap_start) = next_start {
+                if start > gap_start {
+                    result.push(IpRange::from_bits(gap_start, start - 1));
+                }
+            }
+            next_start = end.checked_add(1).filter(|next| *next <= A::max_bits());
+        }
+
+        if let Some(gap_start) = next_start {
+            result.push(IpRange::from_bits(gap_start, A::max_bits()));
+        }
+
+        IpSet { ranges: result }
+    }
+
+    pub fn difference(&self, other: &IpSet<A>) -> IpSet<A> {
+        self.intersection(&other.complement())
+    }
+
+    pub fn is_subset(&self, other: &IpSet<A>) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn is_superset(&self, other: &IpSet<A>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn to_subnets(&self) -> Vec<Subnet<A>> {
+        self.ranges.iter().flat_map(|range| range.to_subnets()).collect()
+    }
+}
+
+impl<A: IpFamily> From<Subnet<A>> for IpSet<A> {
+    fn from(subnet: Subnet<A>) -> Self {
+        IpSet { ranges: vec![IpRange::from(subnet)] }
+    }
+}
+
+impl<A: IpFamily> From<IpRange<A>> for IpSet<A> {
+    fn from(range: IpRange<A>) -> Self {
+        IpSet { ranges: vec![range] }
+    }
+}
+
+impl<A: IpFamily, R: Into<IpRange<A>>> FromIterator<R> for IpSet<A> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        Self::from_intervals(
+            iter.into_iter()
+                .map(|range| {
+                    let range = range.into();
+                    (range.start().to_bits(), range.end().to_bits())
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<A: IpFamily> fmt::Display for IpSet<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranges: Vec<String> = self.ranges.iter().map(|r| r.to_string()).collect();
+        write!(f, "{}", ranges.join(", "))
+    }
+}
+
+impl<A: IpFamily> FromStr for IpSet<A> {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        list_items(input)
+            .map(|item| item.parse::<IpRange<A>>())
+            .collect()
+    }
+}
+
+pub fn address_exclude<A: IpFamily>(base: &Subnet<A>, excluded: &[IpRange<A>]) -> Vec<Subnet<A>> {
+    let excluded: IpSet<A> = excluded.iter().copied().collect();
+    IpSet::from(*base).difference(&excluded).to_subnets()
+}
+
+pub(crate) fn list_items(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split([',', '\n'])
+        .map(|item| item.split('#').next().unwrap_or("").trim())
+        .filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    fn set(input: &str) -> IpSet {
+        input.parse().unwrap()
+    }
+
+    fn cidrs(set: &IpSet) -> Vec<String> {
+        set.to_subnets().iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalization_merges_overlaps_and_neighbours() {
+        let s = set("10.0.0.0/25, 10.0.0.128/25, 10.0.0.5, 10.0.1.0 - 10.0.1.9\n10.0.1.10");
+        assert_eq!(s.ranges().len(), 1);
+        assert_eq!(s.to_string(), "10.0.0.0 - 10.0.1.10");
+        assert_eq!(s.len(), 267.into());
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        let s = set("# allow list\n10.0.0.0/24  # office\n\n192.168.0.1\n");
+        assert_eq!(cidrs(&s), vec!["10.0.0.0/24", "192.168.0.1/32"]);
+        assert!("10.0.0.0/24, bogus".parse::<IpSet>().is_err());
+    }
+
+    #[test]
+    fn test_union_intersection_difference() {
+        let a = set("10.0.0.0/16");
+        let b = set("10.0.1.0/24, 10.0.3.0/24, 172.16.0.0/12");
+
+        assert_eq!(cidrs(&a.union(&b)), vec!["10.0.0.0/16", "172.16.0.0/12"]);
+        assert_eq!(cidrs(&a.intersection(&b)), vec!["10.0.1.0/24", "10.0.3.0/24"]);
+        assert_eq!(
+            cidrs(&a.difference(&set("10.0.1.0/24, 10.0.3.0/24, 10.0.128.0/17"))),
+            vec!["10.0.0.0/24", "10.0.2.0/24", "10.0.4.0/22", "10.0.8.0/21", "10.0.16.0/20", "10.0.32.0/19", "10.0.64.0/18"]
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        assert_eq!(cidrs(&IpSet::new().complement()), vec!["0.0.0.0/0"]);
+        assert!(IpSet::<Ipv4Addr>::full().complement().is_empty());
+        assert_eq!(cidrs(&set("0.0.0.0/1").complement()), vec!["128.0.0.0/1"]);
+        assert_eq!(
+            cidrs(&set("0.0.0.1 - 255.255.255.254").complement()),
+            vec!["0.0.0.0/32", "255.255.255.255/32"]
+        );
+    }
+
+    #[test]
+    fn test_contains_and_subset() {
+        let s = set("10.0.0.0/24, 10.0.2.0/24");
+        assert!(s.contains(Ipv4Addr::new(10, 0, 2, 77)));
+        assert!(!s.contains(Ipv4Addr::new(10, 0, 1, 77)));
+        assert!(set("10.0.0.128/25").is_subset(&s));
+        assert!(!set("10.0.0.0/22").is_subset(&s));
+        assert!(set("10.0.0.0/22").is_superset(&s));
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s = IpSet::new();
+        s.insert("10.0.0.0/25".parse::<Subnet>().unwrap());
+        s.insert_ip(Ipv4Addr::new(10, 0, 0, 128));
+        assert_eq!(s.to_string(), "10.0.0.0 - 10.0.0.128");
+    }
+
+    #[test]
+    fn test_address_exclude() {
+        let excluded: Vec<IpRange> = ["10.0.1.0/24", "10.0.4.0/24", "10.0.200.0/24"]
+            .iter()
+            .map(|r| r.parse().unwrap())
+            .collect();
+        let result = address_exclude(&"10.0.0.0/16".parse::<Subnet>().unwrap(), &excluded);
+
+        assert_eq!(result[0].to_string(), "10.0.0.0/24");
+        assert_eq!(result[1].to_string(), "10.0.2.0/23");
+        assert!(result.windows(2).all(|w| w[0] < w[1]));
+        assert!(excluded.iter().all(|ex| result.iter().all(|s| !s.contains_ip(ex.start()))));
+
+        let total: AddressCount = result.iter().map(|s| s.address_total()).sum();
+        assert_eq!(total, (65536 - 3 * 256).into());
+    }
+
+    #[test]
+    fn test_address_exclude_ranges_and_everything() {
+        let excluded: Vec<IpRange> = vec!["10.0.0.1 - 10.0.0.254".parse().unwrap()];
+        let result: Vec<String> = address_exclude(&"10.0.0.0/24".parse::<Subnet>().unwrap(), &excluded)
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(result, vec!["10.0.0.0/32", "10.0.0.255/32"]);
+
+        let everything: Vec<IpRange> = vec!["10.0.0.0/8".parse().unwrap()];
+        assert!(address_exclude(&"10.0.0.0/24".parse::<Subnet>().unwrap(), &everything).is_empty());
+    }
+
+    #[test]
+    fn test_ipv6_set() {
+        let a: IpSet<Ipv6Addr> = "2001:db8::/32".parse().unwrap();
+        let b: IpSet<Ipv6Addr> = "2001:db8:8000::/33".parse().unwrap();
+        let diff: Vec<String> = a.difference(&b).to_subnets().iter().map(|s| s.to_string()).collect();
+        assert_eq!(diff, vec!["2001:db8::/33"]);
+        assert_eq!(IpSet::<Ipv6Addr>::new().complement().len(), AddressCount::AllIpv6);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl Json {
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    pub fn array<T: ToJson>(items: impl IntoIterator<Item = T>) -> Json {
+        Json::Array(items.into_iter().map(|item| item.to_json()).collect())
+    }
+
+    pub fn string(value: impl fmt::Display) -> Json {
+        Json::String(value.to_string())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn push(&mut self, key: &str, value: impl Into<Json>) {
+        if let Json::Object(fields) = self {
+            fields.push((key.to_string(), value.into()));
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl ToJson for Json {
+    fn to_json(&self) -> Json {
+        self.clone()
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+fn integer(digits: String) -> Json {
+    let magnitude = digits.trim_start_matches('-').parse::<u128>().unwrap_or(u128::MAX);
+    if magnitude <= MAX_SAFE_INTEGER { Json::Number(digits) } else { Json::String(digits) }
+}
+
+macro_rules! json_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Json {
+            fn from(value: $t) -> Self {
+                integer(value.to_string())
+            }
+        })*
+    };
+}
+
+json_number!(u8, u32, u64, usize, u128, i128);
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        if value.is_finite() { Json::Number(value.to_string()) } else { Json::Null }
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Self {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => f.write_str(value),
+            Json::String(value) => write_escaped(f, value),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            },
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let json = Json::object([
+            ("name", "a \"quoted\"\nvalue\u{1}".into()),
+            ("count", 42u32.into()),
+            ("safe", 9_007_199_254_740_991u64.into()),
+            ("big", u128::MAX.into()),
+            ("negative", (-9_007_199_254_740_993i128).into()),
+            ("ratio", 12.3456.into()),
+            ("missing", Option::<u8>::None.into()),
+            ("flags", vec![true, false].into()),
+            ("empty", Json::Object(Vec::new())),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            "{\"name\":\"a \\\"quoted\\\"\\nvalue\\u0001\",\"count\":42,\
+             \"safe\":9007199254740991,\"big\":\"340282366920938463463374607431768211455\",\
+             \"negative\":\"-9007199254740993\",\"ratio\":12.3456,\
+             \"missing\":null,\"flags\":[true,false],\"empty\":{}}"
+        );
+    }
+
+    #[test]
+    fn test_get_and_push() {
+        let mut json = Json::object([("a", 1u8.into())]);
+        json.push("b", "two");
+        assert_eq!(json.get("b"), Some(&Json::String("two".to_string())));
+        assert_eq!(json.get("c"), None);
+        assert_eq!(Json::Null.get("a"), None);
+    }
+}
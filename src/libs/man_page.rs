@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{CommandHelp, GlobalOption, IpCalculatorError};
+
+const PROGRAM: &str = "ip_calculator";
+const SUMMARY: &str = "calculate and manipulate IPv4 and IPv6 addresses, subnets and ranges";
+const DESCRIPTION: &str = "The IP Calculator validates IP addresses, masks and CIDR notations, splits networks \
+                           into subnets, finds address ranges and performs set, aggregation, VLSM, routing and \
+                           reverse DNS operations. Every command that takes a CIDR accepts both IPv4 and IPv6 \
+                           prefixes.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManFormat {
+    Roff,
+    Markdown,
+}
+
+impl FromStr for ManFormat {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "roff" | "man" => Ok(ManFormat::Roff),
+            "markdown" | "md" => Ok(ManFormat::Markdown),
+            _ => Err(IpCalculatorError::ArgumentsError(format!(
+                "Unknown manual format '{}'. Expected roff or markdown.",
+                input
+            ))),
+        }
+    }
+}
+
+pub fn generate(format: ManFormat) -> String {
+    match format {
+        ManFormat::Roff => roff(),
+        ManFormat::Markdown => markdown(),
+    }
+}
+
+fn usage_lines(help: &CommandHelp) -> Vec<String> {
+    help.usage
+        .replace(" or ./", "\n./")
+        .lines()
+        .map(|line| line.trim().replace("./ip_calculator", PROGRAM))
+        .collect()
+}
+
+fn aliases(help: &CommandHelp) -> Vec<&'static str> {
+    if help.aliases.is_empty() {
+        vec![help.name]
+    } else {
+        help.aliases.to_vec()
+    }
+}
+
+fn roff() -> String {
+    let commands = CommandHelp::get_all();
+    let mut lines = vec![
+        format!(
+            ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+            PROGRAM.to_ascii_uppercase(),
+            PROGRAM,
+            env!("CARGO_PKG_VERSION")
+        ),
+        ".SH NAME".to_string(),
+        format!("{} \\- {}", PROGRAM, roff_escape(SUMMARY)),
+        ".SH SYNOPSIS".to_string(),
+        ".nf".to_string(),
+    ];
+    for help in &commands {
+        lines.extend(usage_lines(help).iter().map(|usage| roff_escape(usage)));
+    }
+    lines.push(".fi".to_string());
+    lines.push(".SH DESCRIPTION".to_string());
+    lines.push(roff_escape(DESCRIPTION));
+
+    lines.push(".SH COMMANDS".to_string());
+    for help in &commands {
+        lines.push(format!(".SS \"{}\"", roff_escape(&aliases(help).join(", "))));
+        lines.push(roff_escape(help.long_desc));
+        lines.push(".PP".to_string());
+        lines.push(".B Usage:".to_string());
+        lines.push(".nf".to_string());
+        lines.push(".RS".to_string());
+        lines.extend(usage_lines(help).iter().map(|usage| roff_escape(usage)));
+        lines.push(".RE".to_string());
+        lines.push(".fi".to_string());
+        if !help.examples.is_empty() {
+            lines.push(".PP".to_string());
+            lines.push(".B Examples:".to_string());
+            lines.push(".nf".to_string());
+            lines.push(".RS".to_string());
+            lines.extend(help.examples.iter().map(|example| roff_escape(&example.replace("./ip_calculator", PROGRAM))));
+            lines.push(".RE".to_string());
+            lines.push(".fi".to_string());
+        }
+    }
+
+    lines.push(".SH OPTIONS".to_string());
+    for option in GlobalOption::get_all() {
+        lines.push(".TP".to_string());
+        lines.push(format!(".B {}", roff_escape(&option.names.join(", "))));
+        lines.push(roff_escape(option.description));
+    }
+
+    lines.push(".SH \"EXIT STATUS\"".to_string());
+    for (code, description) in GlobalOption::exit_statuses() {
+        lines.push(".TP".to_string());
+        lines.push(format!(".B {}", code));
+        lines.push(roff_escape(description));
+    }
+    lines.join("\n")
+}
+
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn markdown() -> String {
+    let mut lines = Vec::new();
+    for help in CommandHelp::get_all() {
+        let names: Vec<String> = aliases(&help).iter().map(|alias| format!("**{}**", alias)).collect();
+        lines.push(format!("## {}", help.short_desc));
+        lines.push(format!("- {}: {}", names.join(" or "), markdown_escape(help.long_desc)));
+        lines.push("```bash".to_string());
+        lines.extend(help.usage.replace(" or ./", "\n./").lines().map(|line| line.trim().to_string()));
+        lines.push("```".to_string());
+        if !help.examples.is_empty() {
+            lines.push("Examples:".to_string());
+            lines.push("```bash".to_string());
+            lines.extend(help.examples.iter().map(|example| example.to_string()));
+            lines.push("```".to_string());
+        }
+        lines.push(String::new());
+    }
+
+    lines.push("## Global Options".to_string());
+    for option in GlobalOption::get_all() {
+        let names: Vec<String> = option.names.iter().map(|name| format!("**{}**", name)).collect();
+        lines.push(format!("- {}: {}", names.join(" or "), markdown_escape(option.description)));
+    }
+    lines.push(String::new());
+
+    lines.push("## Exit Status".to_string());
+    for (code, description) in GlobalOption::exit_statuses() {
+        lines.push(format!("- **{}**: {}", code, description));
+    }
+    lines.join("\n")
+}
+
+fn markdown_escape(text: &str) -> String {
+    text.replace('<', "\\<").replace('>', "\\>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roff_sections() {
+        let page = generate(ManFormat::Roff);
+        assert!(page.starts_with(".TH IP_CALCULATOR 1"));
+        for section in [".SH NAME", ".SH SYNOPSIS", ".SH COMMANDS", ".SH OPTIONS", ".SH \"EXIT STATUS\""] {
+            assert!(page.contains(section), "missing {}", section);
+        }
+        assert!(page.contains(".SS \"\\-s, \\-\\-subnets\""));
+        assert!(page.contains("ip_calculator \\-\\-get\\-subnet <CIDR> <new_prefix> <index> [\\-o <file> [\\-\\-columns <list>]]"));
+        assert!(!page.contains("./ip_calculator"));
+    }
+
+    #[test]
+    fn test_markdown_matches_command_help() {
+        let markdown = generate(ManFormat::Markdown);
+        let subnets = CommandHelp::find_by_name_or_alias("subnets").unwrap();
+        assert!(markdown.contains(&format!("## {}\n- **-s** or **--subnets**:", subnets.short_desc)));
+        assert!(markdown.contains(subnets.usage));
+        assert!(markdown.contains("- **2**: The command line could not be parsed."));
+        assert!("html".parse::<ManFormat>().is_err());
+    }
+
+    #[test]
+    fn test_readme_reference_is_up_to_date() {
+        let readme = include_str!("../../README.md");
+        let start = readme.find("<!-- BEGIN GENERATED COMMAND REFERENCE -->\n").unwrap();
+        let end = readme.find("\n<!-- END GENERATED COMMAND REFERENCE -->").unwrap();
+        let generated = &readme[start + "<!-- BEGIN GENERATED COMMAND REFERENCE -->\n".len()..end];
+        assert_eq!(generated, generate(ManFormat::Markdown), "regenerate the README with --generate-man markdown");
+    }
+}
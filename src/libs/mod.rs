@@ -1 +1,18 @@
-pub mod calc_ip;
\ No newline at end of file
+pub mod aggregate;
+pub mod arithmetic;
+pub mod calc_ip;
+pub mod completions;
+pub mod export;
+pub mod ip_range;
+pub mod ip_set;
+pub mod json;
+pub mod man_page;
+pub mod notation;
+pub mod prefix_trie;
+pub mod render;
+pub mod reverse_dns;
+pub mod route_table;
+pub mod special_purpose;
+pub mod subnet_iter;
+pub mod vlsm;
+pub mod zone_file;
\ No newline at end of file
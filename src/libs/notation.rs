@@ -0,0 +1,151 @@
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{IpCalculatorError, IpFamily};
+
+pub fn parse_ipv4_any(input: &str) -> Result<Ipv4Addr, IpCalculatorError> {
+    let input = input.trim().to_ascii_lowercase();
+    let unrecognised = || IpCalculatorError::ConversionError(format!(
+        "Unrecognised IPv4 notation: {} (expected dotted-quad, integer, 0x-prefixed hex or binary)",
+        input
+    ));
+
+    if let Some(hex) = input.strip_prefix("0x") {
+        return parse_digits(hex, 16).ok_or_else(unrecognised);
+    }
+    if let Some(binary) = input.strip_prefix("0b") {
+        return parse_digits(&binary.replace(['.', '_'], ""), 2).ok_or_else(unrecognised);
+    }
+    if is_dotted_binary(&input) {
+        return parse_digits(&input.replace('.', ""), 2).ok_or_else(unrecognised);
+    }
+    if input.contains('.') {
+        return Ipv4Addr::from_str(&input).map_err(|_| unrecognised());
+    }
+    if input.len() == 32 && input.chars().all(|c| c == '0' || c == '1') {
+        return parse_digits(&input, 2).ok_or_else(unrecognised);
+    }
+    if input.chars().all(|c| c.is_ascii_digit()) {
+        return parse_digits(&input, 10).ok_or_else(unrecognised);
+    }
+    if input.chars().all(|c| c.is_ascii_hexdigit()) {
+        return parse_digits(&input, 16).ok_or_else(unrecognised);
+    }
+    Err(unrecognised())
+}
+
+fn parse_digits(digits: &str, radix: u32) -> Option<Ipv4Addr> {
+    if digits.is_empty() {
+        return None;
+    }
+    u32::from_str_radix(digits, radix).ok().map(Ipv4Addr::from)
+}
+
+fn is_dotted_binary(input: &str) -> bool {
+    let groups: Vec<&str> = input.split('.').collect();
+    groups.len() == 4
+        && groups
+            .iter()
+            .all(|group| group.len() == 8 && group.chars().all(|c| c == '0' || c == '1'))
+}
+
+pub fn to_integer(ip: Ipv4Addr) -> u32 {
+    u32::from(ip)
+}
+
+pub fn to_hex(ip: Ipv4Addr) -> String {
+    format!("0x{:08X}", u32::from(ip))
+}
+
+pub fn to_binary<A: IpFamily>(ip: A) -> String {
+    binary_string(ip.to_bits(), A::BITS)
+}
+
+pub fn split_binary<A: IpFamily>(ip: A, prefix: u8) -> (String, String) {
+    let binary = to_binary(ip);
+    let group = group_size(A::BITS) as usize;
+    let prefix = prefix.min(A::BITS) as usize;
+    let offset = if prefix == 0 { 0 } else { prefix + (prefix - 1) / group };
+    let (network, host) = binary.split_at(offset);
+    (network.to_string(), host.trim_start_matches(['.', ':']).to_string())
+}
+
+pub(crate) fn binary_string(bits: u128, width: u8) -> String {
+    let separator = if width == 32 { '.' } else { ':' };
+    let group = group_size(width);
+    let mut binary = String::with_capacity(width as usize + width as usize / group as usize);
+    for i in 0..width {
+        if i > 0 && i % group == 0 {
+            binary.push(separator);
+        }
+        binary.push(if (bits >> (width - 1 - i)) & 1 == 1 { '1' } else { '0' });
+    }
+    binary
+}
+
+pub(crate) fn group_size(width: u8) -> u8 {
+    if width == 32 { 8 } else { 16 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_parse_every_notation() {
+        let expected = Ipv4Addr::new(10, 0, 0, 1);
+        for input in [
+            "10.0.0.1",
+            "167772161",
+            "0x0A000001",
+            "0x0a000001",
+            "0A000001",
+            "0b00001010000000000000000000000001",
+            "00001010000000000000000000000001",
+            "00001010.00000000.00000000.00000001",
+            "0b00001010_00000000_00000000_00000001",
+        ] {
+            assert_eq!(parse_ipv4_any(input).unwrap(), expected, "{}", input);
+        }
+        assert_eq!(parse_ipv4_any("4294967295").unwrap(), Ipv4Addr::BROADCAST);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        for input in ["4294967296", "0x100000000", "0b2", "0x", "10.0.0", "hello", "10.0.0.256"] {
+            assert!(
+                matches!(parse_ipv4_any(input), Err(IpCalculatorError::ConversionError(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_notations() {
+        let mask = Ipv4Addr::new(255, 255, 240, 0);
+        assert_eq!(to_integer(mask), 4294963200);
+        assert_eq!(to_hex(mask), "0xFFFFF000");
+        assert_eq!(to_binary(mask), "11111111.11111111.11110000.00000000");
+    }
+
+    #[test]
+    fn test_split_binary() {
+        let ip = Ipv4Addr::new(192, 168, 1, 0);
+        assert_eq!(
+            split_binary(ip, 24),
+            ("11000000.10101000.00000001".to_string(), "00000000".to_string())
+        );
+        assert_eq!(
+            split_binary(ip, 20),
+            ("11000000.10101000.0000".to_string(), "0001.00000000".to_string())
+        );
+        assert_eq!(split_binary(ip, 0).0, "");
+        assert_eq!(split_binary(ip, 32).1, "");
+
+        let (network, host) = split_binary(Ipv6Addr::LOCALHOST, 64);
+        assert_eq!(network.len(), 64 + 3);
+        assert!(host.ends_with('1'));
+    }
+}
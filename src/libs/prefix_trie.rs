@@ -0,0 +1,195 @@
+use std::marker::PhantomData;
+
+use crate::libs::calc_ip::{IpFamily, Subnet};
+
+const ROOT: usize = 0;
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    children: [Option<usize>; 2],
+    value: Option<V>,
+}
+
+impl<V> Node<V> {
+    fn empty() -> Self {
+        Node { children: [None, None], value: None }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefixTrie<A: IpFamily, V> {
+    nodes: Vec<Node<V>>,
+    len: usize,
+    _family: PhantomData<A>,
+}
+
+impl<A: IpFamily, V> Default for PrefixTrie<A, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: IpFamily, V> PrefixTrie<A, V> {
+    pub fn new() -> Self {
+        PrefixTrie {
+            nodes: vec![Node::empty()],
+            len: 0,
+            _family: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn bit(bits: u128, depth: u8) -> usize {
+        ((bits >> (A::BITS - 1 - depth)) & 1) as usize
+    }
+
+    fn find_node(&self, prefix: &Subnet<A>) -> Option<usize> {
+        let bits = prefix.network.to_bits();
+        (0..prefix.prefix).try_fold(ROOT, |node, depth| self.nodes[node].children[Self::bit(bits, depth)])
+    }
+
+    pub fn insert(&mut self, prefix: Subnet<A>, value: V) -> Option<V> {
+        let bits = prefix.network.to_bits();
+        let mut node = ROOT;
+        for depth in 0..prefix.prefix {
+            let bit = Self::bit(bits, depth);
+            node = match self.nodes[node].children[bit] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node::empty());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit] = Some(child);
+                    child
+                }
+            };
+        }
+
+        let previous = self.nodes[node].value.replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn get(&self, prefix: &Subnet<A>) -> Option<&V> {
+        self.find_node(prefix).and_then(|node| self.nodes[node].value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, prefix: &Subnet<A>) -> Option<&mut V> {
+        self.find_node(prefix).and_then(|node| self.nodes[node].value.as_mut())
+    }
+
+    pub fn get_or_insert_with(&mut self, prefix: Subnet<A>, default: impl FnOnce() -> V) -> &mut V {
+        if self.get(&prefix).is_none() {
+            self.insert(prefix, default());
+        }
+        self.get_mut(&prefix).expect("prefix was just inserted")
+    }
+
+    fn path(&self, bits: u128) -> impl Iterator<Item = (u8, &V)> {
+        std::iter::successors(Some((0, ROOT)), move |&(depth, node)| {
+            if depth == A::BITS {
+                return None;
+            }
+            self.nodes[node].children[Self::bit(bits, depth)].map(|child| (depth + 1, child))
+        })
+        .filter_map(|(depth, node)| self.nodes[node].value.as_ref().map(|value| (depth, value)))
+    }
+
+    pub fn matches(&self, ip: A) -> Vec<(Subnet<A>, &V)> {
+        let bits = ip.to_bits();
+        self.path(bits)
+            .map(|(depth, value)| (Subnet::from_bits(bits, depth), value))
+            .collect()
+    }
+
+    pub fn longest_match(&self, ip: A) -> Option<(Subnet<A>, &V)> {
+        let bits = ip.to_bits();
+        self.path(bits)
+            .last()
+            .map(|(depth, value)| (Subnet::from_bits(bits, depth), value))
+    }
+}
+
+impl<A: IpFamily, V> FromIterator<(Subnet<A>, V)> for PrefixTrie<A, V> {
+    fn from_iter<I: IntoIterator<Item = (Subnet<A>, V)>>(iter: I) -> Self {
+        let mut trie = PrefixTrie::new();
+        for (prefix, value) in iter {
+            trie.insert(prefix, value);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_longest_match() {
+        let trie: PrefixTrie<Ipv4Addr, &str> = [
+            ("0.0.0.0/0".parse::<Subnet>().unwrap(), "default"),
+            ("10.0.0.0/8".parse::<Subnet>().unwrap(), "ten"),
+            ("10.1.0.0/16".parse::<Subnet>().unwrap(), "ten-one"),
+            ("10.1.2.3/32".parse::<Subnet>().unwrap(), "host"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(trie.len(), 4);
+        let lookup = |ip: &str| trie.longest_match(ip.parse().unwrap()).map(|(p, v)| (p.to_string(), *v));
+        assert_eq!(lookup("10.1.2.3"), Some(("10.1.2.3/32".to_string(), "host")));
+        assert_eq!(lookup("10.1.2.4"), Some(("10.1.0.0/16".to_string(), "ten-one")));
+        assert_eq!(lookup("10.200.0.1"), Some(("10.0.0.0/8".to_string(), "ten")));
+        assert_eq!(lookup("192.168.0.1"), Some(("0.0.0.0/0".to_string(), "default")));
+        assert_eq!(trie.matches("10.1.2.3".parse().unwrap()).len(), 4);
+    }
+
+    #[test]
+    fn test_insert_get_and_replace() {
+        let mut trie = PrefixTrie::new();
+        assert!(trie.is_empty());
+        assert_eq!(trie.insert("192.168.0.0/24".parse::<Subnet>().unwrap(), 1), None);
+        assert_eq!(trie.insert("192.168.0.0/24".parse::<Subnet>().unwrap(), 2), Some(1));
+        assert_eq!(trie.len(), 1);
+        assert_eq!(trie.get(&"192.168.0.0/24".parse::<Subnet>().unwrap()), Some(&2));
+        assert_eq!(trie.get(&"192.168.0.0/23".parse::<Subnet>().unwrap()), None);
+        assert_eq!(trie.get(&"192.168.0.0/25".parse::<Subnet>().unwrap()), None);
+        assert!(trie.longest_match(Ipv4Addr::new(192, 168, 1, 1)).is_none());
+
+        trie.get_or_insert_with("192.168.0.0/23".parse::<Subnet>().unwrap(), || 0);
+        *trie.get_or_insert_with("192.168.0.0/23".parse::<Subnet>().unwrap(), || 0) += 5;
+        assert_eq!(trie.get(&"192.168.0.0/23".parse::<Subnet>().unwrap()), Some(&5));
+    }
+
+    #[test]
+    fn test_many_prefixes() {
+        let trie: PrefixTrie<Ipv4Addr, u32> = (0..200_000u32)
+            .map(|i| (Subnet::new(Ipv4Addr::from(0x0A00_0000 + (i << 5)), 27).unwrap(), i))
+            .collect();
+        assert_eq!(trie.len(), 200_000);
+        let (prefix, value) = trie.longest_match(Ipv4Addr::from(0x0A00_0000 + (123_456 << 5) + 7)).unwrap();
+        assert_eq!(*value, 123_456);
+        assert_eq!(prefix.prefix, 27);
+    }
+
+    #[test]
+    fn test_ipv6_trie() {
+        let mut trie = PrefixTrie::new();
+        trie.insert("::/0".parse::<Subnet<Ipv6Addr>>().unwrap(), "default");
+        trie.insert("2001:db8::/32".parse().unwrap(), "doc");
+        trie.insert("2001:db8::1/128".parse().unwrap(), "host");
+        let ip = |s: &str| s.parse::<Ipv6Addr>().unwrap();
+        assert_eq!(trie.longest_match(ip("2001:db8::1")).map(|(_, v)| *v), Some("host"));
+        assert_eq!(trie.longest_match(ip("2001:db8::2")).map(|(_, v)| *v), Some("doc"));
+        assert_eq!(trie.longest_match(ip("2001:db9::")).map(|(_, v)| *v), Some("default"));
+    }
+}
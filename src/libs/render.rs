@@ -0,0 +1,935 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::net::Ipv4Addr;
+
+use crate::libs::aggregate::reduction_ratio;
+use crate::libs::calc_ip::{
+    cidr_to_mask, vlsm_totals, AddressCount, CommandHelp, CommandOutput, FamilyOutput, IpCalculatorError,
+    IpFamily, InputType, MaskDiagnostic, RouteMatch, SetOutcome, Subnet, SubnetListing,
+};
+use crate::libs::export::{self, Column, ExportFormat};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::notation::{split_binary, to_binary, to_hex, to_integer};
+
+pub(crate) const SEPARATOR: &str = "----------------------------";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    Colored,
+}
+
+impl Style {
+    fn paint(self, code: &str, text: impl fmt::Display) -> String {
+        match self {
+            Style::Plain => text.to_string(),
+            Style::Colored => format!("\x1b[{}m{}\x1b[0m", code, text),
+        }
+    }
+
+    pub(crate) fn label(self, text: impl fmt::Display) -> String {
+        self.paint("1;34", text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Colored,
+    Json,
+    Csv,
+    Tsv,
+}
+
+pub fn render(output: &CommandOutput, format: Format) -> String {
+    match format {
+        Format::Text => render_text(output, Style::Plain),
+        Format::Colored => render_text(output, Style::Colored),
+        Format::Json => output.to_json().to_string(),
+        Format::Csv => render_table(output, ExportFormat::Csv, &Column::ALL),
+        Format::Tsv => render_table(output, ExportFormat::Tsv, &Column::ALL),
+    }
+}
+
+pub fn render_text(output: &CommandOutput, style: Style) -> String {
+    let lines = match output {
+        CommandOutput::V4(output) => family_text(output, style),
+        CommandOutput::V6(output) => family_text(output, style),
+        CommandOutput::SameSubnet(check) => vec![format!(
+            "IP addresses {} and {} {} in the same subnet",
+            check.ip1,
+            check.ip2,
+            if check.same_subnet { "are" } else { "are not" }
+        )],
+        CommandOutput::CheckIp(ip) => vec![format!("IP address {} is valid", ip)],
+        CommandOutput::CheckMask(check) => vec![
+            format!("Subnet mask {} is valid", check.mask),
+            format!("Notation: {}", notation_name(&check.notation)),
+            format!("Prefix: /{}", check.prefix),
+            format!("Netmask: {}", check.netmask),
+            format!("Wildcard: {}", check.wildcard),
+        ],
+        CommandOutput::Lookup { routes_loaded, matches } => {
+            let mut lines = vec![format!("{} {}", style.label("Routes loaded:"), routes_loaded)];
+            for route_match in matches {
+                let (ip, route) = match route_match {
+                    RouteMatch::V4(ip, route) => (ip.to_string(), route.as_ref().map(ToString::to_string)),
+                    RouteMatch::V6(ip, route) => (ip.to_string(), route.as_ref().map(ToString::to_string)),
+                };
+                lines.push(format!("{} -> {}", ip, route.unwrap_or_else(|| "no route".to_string())));
+            }
+            lines
+        },
+        CommandOutput::Classify(results) => results
+            .iter()
+            .map(|result| format!("{}: {} | {}", result.input, result.classification, result.classification.flags()))
+            .collect(),
+        CommandOutput::Convert(results) => {
+            let mut lines = Vec::new();
+            for (i, conversion) in results.iter().enumerate() {
+                if i > 0 {
+                    lines.push(SEPARATOR.to_string());
+                }
+                let ip = conversion.ip;
+                lines.push(format!("{}  {}", style.label("Dotted:"), ip));
+                lines.push(format!("{} {}", style.label("Integer:"), to_integer(ip)));
+                lines.push(format!("{}     {}", style.label("Hex:"), to_hex(ip)));
+                lines.push(format!("{}  {}", style.label("Binary:"), to_binary(ip)));
+                if let Some((kind, prefix)) = conversion.mask {
+                    lines.push(format!("{}    valid {} (/{})", style.label("Mask:"), kind, prefix));
+                }
+            }
+            lines
+        },
+    };
+    lines.join("\n")
+}
+
+fn family_text<A: IpFamily>(output: &FamilyOutput<A>, style: Style) -> Vec<String> {
+    let mut lines = Vec::new();
+    match output {
+        FamilyOutput::Subnets(listing) => {
+            for subnet in &listing.subnets {
+                lines.push(subnet_details(subnet, style));
+                lines.push(SEPARATOR.to_string());
+            }
+            if listing.page_clamped() {
+                lines.push(style.paint(
+                    "1;33",
+                    format!("Warning: Only {} pages available. Showing last page.", listing.total_pages),
+                ));
+            }
+            lines.push(format!(
+                "\nTotal subnets: {} | Subnets displayed: {}",
+                listing.total_subnets,
+                listing.subnets.len()
+            ));
+            if listing.total_pages > 0 {
+                lines.push(format!("Page {}/{}", listing.page + 1, listing.total_pages));
+            }
+            lines.extend(written_to(&listing.output_file));
+        },
+        FamilyOutput::GetSubnet(selection) => {
+            if selection.clamped() {
+                lines.push(format!(
+                    "Warning: Requested subnet index {} exceeds available subnets ({})",
+                    selection.requested_index, selection.total_subnets
+                ));
+            }
+            lines.push(subnet_details(&selection.subnet, style));
+            lines.extend(written_to(&selection.output_file));
+        },
+        FamilyOutput::FindRange { start, end, .. } => {
+            lines.push(format!("Available IP range: {} - {}", start, end));
+        },
+        FamilyOutput::Display { subnet, verbose, output_file } => {
+            if *verbose {
+                lines.push(verbose_details(subnet, style));
+            } else {
+                lines.push(subnet_details(subnet, style));
+            }
+            lines.extend(written_to(output_file));
+        },
+        FamilyOutput::RangeToCidr { range, subnets, output_file } => {
+            lines.push(format!("{} {} ({} addresses)", style.label("Range:"), range, range.len()));
+            lines.extend(subnets.iter().map(ToString::to_string));
+            lines.push(format!("\nTotal CIDRs: {}", subnets.len()));
+            lines.extend(written_to(output_file));
+        },
+        FamilyOutput::Set { operation, outcome } => match outcome {
+            SetOutcome::Subset(is_subset) => lines.push(format!(
+                "The first list {} a subset of the second list",
+                if *is_subset { "is" } else { "is not" }
+            )),
+            SetOutcome::Set(result) => {
+                let subnets = result.to_subnets();
+                lines.push(format!(
+                    "{} {} CIDRs, {} addresses",
+                    style.label(format!("Result ({}):", operation)),
+                    subnets.len(),
+                    result.len()
+                ));
+                lines.extend(subnets.iter().map(ToString::to_string));
+            },
+        },
+        FamilyOutput::Aggregate { input_count, summaries, summarized } => {
+            let ratio = reduction_ratio(*input_count, summaries.len());
+            if *summarized {
+                let extra: AddressCount = summaries.iter().map(|s| s.extra_addresses).sum();
+                lines.push(format!(
+                    "{} {} prefixes -> {} prefixes ({:.2}% reduction, {} extra addresses)",
+                    style.label("Summarized:"), input_count, summaries.len(), ratio, extra
+                ));
+            } else {
+                lines.push(format!(
+                    "{} {} prefixes -> {} prefixes ({:.2}% reduction)",
+                    style.label("Aggregated:"), input_count, summaries.len(), ratio
+                ));
+            }
+            for summary in summaries {
+                if summary.extra_addresses > AddressCount::ZERO {
+                    lines.push(format!("{} (+{} extra addresses)", summary.subnet, summary.extra_addresses));
+                } else {
+                    lines.push(summary.subnet.to_string());
+                }
+            }
+        },
+        FamilyOutput::Supernet { supernet, inputs, extra_addresses } => {
+            lines.push(subnet_details(supernet, style));
+            lines.push(format!("\nInputs: {} | Extra addresses covered: {}", inputs, extra_addresses));
+        },
+        FamilyOutput::Exclude { base, remaining, output_file } => {
+            let addresses = remaining
+                .iter()
+                .map(Subnet::address_total)
+                .sum::<AddressCount>();
+            lines.push(format!(
+                "{} {} CIDRs, {} addresses",
+                style.label(format!("Remaining in {}:", base)),
+                remaining.len(),
+                addresses
+            ));
+            lines.extend(remaining.iter().map(|subnet| format!("{} ({} hosts)", subnet, subnet.num_hosts)));
+            lines.extend(written_to(output_file));
+        },
+        FamilyOutput::Vlsm { parent, allocations } => {
+            let header = ["Name", "Network", "Prefix", "First Usable", "Last Usable", "Hosts", "Wasted"];
+            let rows: Vec<[String; 7]> = allocations
+                .iter()
+                .map(|allocation| {
+                    let usable = |ip: Option<A>| ip.map_or("N/A".to_string(), |ip| ip.to_string());
+                    [
+                        allocation.name.clone(),
+                        allocation.subnet.network.to_string(),
+                        format!("/{}", allocation.subnet.prefix),
+                        usable(allocation.subnet.first_usable),
+                        usable(allocation.subnet.last_usable),
+                        format!("{}/{}", allocation.hosts, allocation.subnet.num_hosts),
+                        allocation.wasted.to_string(),
+                    ]
+                })
+                .collect();
+
+            let mut widths = header.map(str::len);
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+            let format_row = |cells: &[&str]| {
+                cells
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+
+            lines.push(style.label(format_row(&header)));
+            for row in &rows {
+                lines.push(format_row(&row.each_ref().map(String::as_str)));
+            }
+
+            let (wasted, free) = vlsm_totals(parent, allocations);
+            lines.push(format!(
+                "\nSubnets: {} | Wasted hosts: {} | Free addresses in {}: {}",
+                allocations.len(),
+                wasted,
+                parent,
+                free
+            ));
+        },
+        FamilyOutput::Arithmetic { result, .. } => lines.push(result.to_string()),
+        FamilyOutput::ReverseDns(report) => {
+            lines.push(style.label(format!("Reverse Zones for {}:", report.subnet)));
+            lines.extend(report.zones.iter().cloned());
+
+            if let Some(zone) = &report.classless_zone {
+                lines.push(SEPARATOR.to_string());
+                lines.push(format!("{} {}", style.label("Classless Delegation (RFC 2317):"), zone));
+                lines.push("CNAME records for the parent zone:".to_string());
+                lines.extend(report.cname_records.iter().map(ToString::to_string));
+            }
+
+            lines.push(SEPARATOR.to_string());
+            lines.push(style.label("PTR Owner Names:"));
+            for (ip, owner) in &report.ptr_owners {
+                lines.push(format!("{:<15} {}", ip.to_string(), owner));
+            }
+            let shown = AddressCount::from(report.ptr_owners.len() as u128);
+            if report.total_addresses > shown {
+                lines.push(format!("... {} more (use -f to show more)", report.total_addresses.saturating_sub(shown)));
+            }
+        },
+        FamilyOutput::PtrZone { zone, output_file } => match output_file {
+            Some(path) => lines.push(format!(
+                "{} PTR records for {} written to {}",
+                zone.records.len(), zone.origin, path
+            )),
+            None => lines.push(zone.to_string().trim_end().to_string()),
+        },
+    }
+    lines
+}
+
+fn written_to(output_file: &Option<String>) -> Option<String> {
+    output_file.as_ref().map(|path| format!("Output written to {}", path))
+}
+
+pub fn warnings(output: &CommandOutput) -> Vec<String> {
+    let unnamed = match output {
+        CommandOutput::V4(FamilyOutput::PtrZone { zone, .. }) => zone.unnamed,
+        CommandOutput::V6(FamilyOutput::PtrZone { zone, .. }) => zone.unnamed,
+        _ => AddressCount::ZERO,
+    };
+    if unnamed > AddressCount::ZERO {
+        vec![format!("Skipped {} hosts with no name in the CSV", unnamed)]
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn result_value(output: &CommandOutput) -> String {
+    match output {
+        CommandOutput::V4(output) => family_value(output),
+        CommandOutput::V6(output) => family_value(output),
+        CommandOutput::SameSubnet(check) => check.same_subnet.to_string(),
+        CommandOutput::CheckIp(ip) => ip.clone(),
+        CommandOutput::CheckMask(check) => check.netmask.to_string(),
+        CommandOutput::Lookup { matches, .. } => join(matches.iter().filter_map(|route_match| match route_match {
+            RouteMatch::V4(_, route) => route.as_ref().map(|route| route.prefix.to_string()),
+            RouteMatch::V6(_, route) => route.as_ref().map(|route| route.prefix.to_string()),
+        })),
+        CommandOutput::Classify(results) => join(results.iter().map(|result| result.classification.name)),
+        CommandOutput::Convert(results) => join(results.iter().map(|conversion| to_integer(conversion.ip))),
+    }
+}
+
+fn family_value<A: IpFamily>(output: &FamilyOutput<A>) -> String {
+    match output {
+        FamilyOutput::Subnets(listing) => join(&listing.subnets),
+        FamilyOutput::GetSubnet(selection) => selection.subnet.to_string(),
+        FamilyOutput::FindRange { start, end, .. } => format!("{}-{}", start, end),
+        FamilyOutput::Display { subnet, .. } => subnet.to_string(),
+        FamilyOutput::RangeToCidr { subnets, .. } => join(subnets),
+        FamilyOutput::Set { outcome, .. } => match outcome {
+            SetOutcome::Subset(is_subset) => is_subset.to_string(),
+            SetOutcome::Set(result) => join(result.to_subnets()),
+        },
+        FamilyOutput::Aggregate { summaries, .. } => join(summaries.iter().map(|summary| summary.subnet)),
+        FamilyOutput::Supernet { supernet, .. } => supernet.to_string(),
+        FamilyOutput::Exclude { remaining, .. } => join(remaining),
+        FamilyOutput::Vlsm { allocations, .. } => join(allocations.iter().map(|allocation| allocation.subnet)),
+        FamilyOutput::Arithmetic { result, .. } => result.to_string(),
+        FamilyOutput::ReverseDns(report) => match &report.classless_zone {
+            Some(zone) => zone.clone(),
+            None => join(&report.zones),
+        },
+        FamilyOutput::PtrZone { zone, .. } => zone.origin.clone(),
+    }
+}
+
+fn join<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+    items.into_iter().map(|item| item.to_string()).collect::<Vec<_>>().join(",")
+}
+
+pub fn export<W: Write>(
+    writer: &mut W,
+    output: &CommandOutput,
+    format: ExportFormat,
+    columns: &[Column],
+) -> io::Result<()> {
+    match output {
+        CommandOutput::V4(FamilyOutput::PtrZone { zone, .. }) => write!(writer, "{}", zone),
+        CommandOutput::V6(FamilyOutput::PtrZone { zone, .. }) => write!(writer, "{}", zone),
+        CommandOutput::V4(FamilyOutput::Subnets(listing)) => export::write_subnets(writer, all_subnets(listing), format, columns),
+        CommandOutput::V6(FamilyOutput::Subnets(listing)) => export::write_subnets(writer, all_subnets(listing), format, columns),
+        CommandOutput::V4(output) => export::write_subnets(writer, rows(output).into_iter(), format, columns),
+        CommandOutput::V6(output) => export::write_subnets(writer, rows(output).into_iter(), format, columns),
+        _ => export::write_subnets::<Ipv4Addr, W>(writer, std::iter::empty(), format, columns),
+    }
+}
+
+pub fn render_table(output: &CommandOutput, format: ExportFormat, columns: &[Column]) -> String {
+    let mut buffer = Vec::new();
+    let written = match output {
+        CommandOutput::V4(output) => export::write_subnets(&mut buffer, rows(output).into_iter(), format, columns),
+        CommandOutput::V6(output) => export::write_subnets(&mut buffer, rows(output).into_iter(), format, columns),
+        _ => export::write_subnets::<Ipv4Addr, _>(&mut buffer, std::iter::empty(), format, columns),
+    };
+    written.expect("writing to memory cannot fail");
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+fn all_subnets<A: IpFamily>(listing: &SubnetListing<A>) -> impl Iterator<Item = (u128, Subnet<A>)> {
+    listing
+        .base
+        .subnets(listing.prefix)
+        .into_iter()
+        .flatten()
+        .take(listing.filter.unwrap_or(usize::MAX))
+        .enumerate()
+        .map(|(index, subnet)| (index as u128, subnet))
+}
+
+fn rows<A: IpFamily>(output: &FamilyOutput<A>) -> Vec<(u128, Subnet<A>)> {
+    let numbered = |subnets: &[Subnet<A>]| subnets.iter().copied().enumerate().map(|(i, s)| (i as u128, s)).collect();
+    match output {
+        FamilyOutput::Subnets(listing) => listing.subnets.iter().map(|s| (listing.index_of(s), *s)).collect(),
+        FamilyOutput::GetSubnet(selection) => vec![(selection.index(), selection.subnet)],
+        FamilyOutput::Display { subnet, .. } => vec![(0, *subnet)],
+        FamilyOutput::Supernet { supernet, .. } => vec![(0, *supernet)],
+        FamilyOutput::RangeToCidr { subnets, .. } => numbered(subnets),
+        FamilyOutput::Exclude { remaining, .. } => numbered(remaining),
+        FamilyOutput::Aggregate { summaries, .. } => {
+            summaries.iter().enumerate().map(|(i, s)| (i as u128, s.subnet)).collect()
+        },
+        FamilyOutput::Vlsm { allocations, .. } => {
+            allocations.iter().enumerate().map(|(i, a)| (i as u128, a.subnet)).collect()
+        },
+        FamilyOutput::Set { outcome: SetOutcome::Set(result), .. } => numbered(&result.to_subnets()),
+        _ => Vec::new(),
+    }
+}
+
+fn notation_name(notation: &InputType) -> &'static str {
+    match notation {
+        InputType::Cidr => "prefix length",
+        InputType::Mask => "netmask",
+        InputType::Wildcard => "wildcard mask",
+    }
+}
+
+pub fn subnet_details<A: IpFamily>(subnet: &Subnet<A>, style: Style) -> String {
+    let classification = subnet.classify();
+    let mut fields = vec![("Network", subnet.network.to_string())];
+    if A::HAS_BROADCAST {
+        let usable = |ip: Option<A>| ip.map_or("N/A".to_string(), |ip| ip.to_string());
+        fields.extend([
+            ("Mask", subnet.mask.to_string()),
+            ("Wildcard", subnet.wildcard.to_string()),
+            ("Cidr", subnet.prefix.to_string()),
+            ("Broadcast", subnet.broadcast.to_string()),
+            ("First", usable(subnet.first_usable)),
+            ("Last", usable(subnet.last_usable)),
+            ("Hosts", subnet.num_hosts.to_string()),
+        ]);
+    } else {
+        fields.extend([
+            ("Cidr", subnet.prefix.to_string()),
+            ("First", subnet.network.to_string()),
+            ("Last", subnet.broadcast.to_string()),
+            ("Addresses", subnet.address_total().to_string()),
+        ]);
+    }
+    fields.extend([
+        ("Type", classification.to_string()),
+        ("Forwardable", classification.forwardable_text().to_string()),
+        ("Globally Reachable", classification.globally_reachable_text().to_string()),
+    ]);
+
+    fields
+        .into_iter()
+        .map(|(label, value)| format!("{} {}", style.label(format!("{}:", label)), value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn verbose_details<A: IpFamily>(subnet: &Subnet<A>, style: Style) -> String {
+    let mut lines = vec![
+        subnet_details(subnet, style),
+        format!(
+            "{} ({} | {}):",
+            style.label("Binary"),
+            style.paint("32", "network"),
+            style.paint("33", "host")
+        ),
+    ];
+
+    let last_label = if A::HAS_BROADCAST { "Broadcast" } else { "Last" };
+    for (label, ip) in [("Network", subnet.network), ("Mask", subnet.mask), (last_label, subnet.broadcast)] {
+        let (network, host) = split_binary(ip, subnet.prefix);
+        lines.push(format!(
+            "  {} {}|{}",
+            style.label(format!("{:<10}", format!("{}:", label))),
+            style.paint("32", network),
+            style.paint("33", host)
+        ));
+    }
+    lines.join("\n")
+}
+
+pub struct ColoredSubnet<'a, A: IpFamily = Ipv4Addr>(pub &'a Subnet<A>);
+
+impl<A: IpFamily> fmt::Display for ColoredSubnet<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", subnet_details(self.0, Style::Colored))
+    }
+}
+
+pub struct VerboseSubnet<'a, A: IpFamily = Ipv4Addr>(pub &'a Subnet<A>);
+
+impl<A: IpFamily> fmt::Display for VerboseSubnet<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", verbose_details(self.0, Style::Colored))
+    }
+}
+
+impl<A: IpFamily> Subnet<A> {
+    pub fn colored(&self) -> ColoredSubnet<'_, A> {
+        ColoredSubnet(self)
+    }
+
+    pub fn verbose(&self) -> VerboseSubnet<'_, A> {
+        VerboseSubnet(self)
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        subnet_details(self, Style::Plain)
+    }
+}
+
+pub fn render_error(error: &IpCalculatorError, style: Style) -> String {
+    match error {
+        IpCalculatorError::NonContiguousMask(diagnostic) => {
+            format!("{}\nError: {}", mask_diagnostic(diagnostic, style), error)
+        },
+        _ => format!("Error: {}", error),
+    }
+}
+
+fn mask_diagnostic(diagnostic: &MaskDiagnostic, style: Style) -> String {
+    let binary = diagnostic.binary();
+    let marker = diagnostic.marker();
+    let offset = marker.len() - 1;
+    let [shorter, longer] = diagnostic.suggested_prefixes();
+    let nearest = |prefix: u8| cidr_to_mask(prefix).map_or("N/A".to_string(), |mask| mask.to_string());
+
+    [
+        format!("Subnet mask {} is not contiguous", diagnostic.mask),
+        format!(
+            "Binary: {}{}{}",
+            &binary[..offset],
+            style.paint("1;31", &binary[offset..offset + 1]),
+            &binary[offset + 1..]
+        ),
+        format!("        {} first offending bit (bit {} of {})", marker, diagnostic.position + 1, diagnostic.width),
+        format!(
+            "Nearest valid masks: {} (/{}), {} (/{})",
+            nearest(shorter), shorter, nearest(longer), longer
+        ),
+    ]
+    .join("\n")
+}
+
+pub fn command_list(style: Style) -> String {
+    let mut lines = vec![style.paint("1;32", "IP Calculator - Available Commands:"), String::new()];
+    for cmd in CommandHelp::get_all() {
+        let aliases = if cmd.aliases.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", cmd.aliases.join(", "))
+        };
+        lines.push(style.label(format!("{}{}", cmd.name, aliases)));
+        lines.push(format!("    {}\n", cmd.short_desc));
+    }
+    lines.push("\nFor detailed help on a specific command, use: ./ip_calculator --help <command>".to_string());
+    lines.push("Add --json to any command to get a single JSON document instead of text.".to_string());
+    lines.push("Add -q or --quiet to suppress all output and rely on the exit status.".to_string());
+    lines.push("Exit status: 0 true or valid, 1 false or invalid, 2 usage error.".to_string());
+    lines.join("\n")
+}
+
+pub fn command_help(help: &CommandHelp, style: Style) -> String {
+    let mut lines = vec![style.paint("1;32", format!("Help for command: {}", help.name)), String::new()];
+    if !help.aliases.is_empty() {
+        lines.push(format!("{} {}\n", style.label("Aliases:"), help.aliases.join(", ")));
+    }
+    lines.push(style.label("Description:"));
+    lines.push(format!("{}\n", help.long_desc));
+    lines.push(style.label("Usage:"));
+    lines.push(format!("{}\n", help.usage));
+    lines.push(style.label("Examples:"));
+    lines.extend(help.examples.iter().map(|example| format!("  {}", example)));
+    lines.join("\n")
+}
+
+impl CommandOutput {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommandOutput::V4(output) => output.name(),
+            CommandOutput::V6(output) => output.name(),
+            CommandOutput::SameSubnet(_) => "same-subnet",
+            CommandOutput::CheckIp(_) => "check-ip",
+            CommandOutput::CheckMask(_) => "check-mask",
+            CommandOutput::Lookup { .. } => "lookup",
+            CommandOutput::Classify(_) => "classify",
+            CommandOutput::Convert(_) => "convert",
+        }
+    }
+}
+
+impl<A: IpFamily> FamilyOutput<A> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FamilyOutput::Subnets(_) => "subnets",
+            FamilyOutput::GetSubnet(_) => "get-subnet",
+            FamilyOutput::FindRange { .. } => "find-range",
+            FamilyOutput::Display { .. } => "display",
+            FamilyOutput::RangeToCidr { .. } => "range-to-cidr",
+            FamilyOutput::Set { .. } => "set",
+            FamilyOutput::Aggregate { .. } => "aggregate",
+            FamilyOutput::Supernet { .. } => "supernet",
+            FamilyOutput::Exclude { .. } => "exclude",
+            FamilyOutput::Vlsm { .. } => "vlsm",
+            FamilyOutput::Arithmetic { .. } => "ip-math",
+            FamilyOutput::ReverseDns(_) => "reverse-dns",
+            FamilyOutput::PtrZone { .. } => "ptr-zone",
+        }
+    }
+}
+
+impl ToJson for CommandOutput {
+    fn to_json(&self) -> Json {
+        let mut body = match self {
+            CommandOutput::V4(output) => output.to_json(),
+            CommandOutput::V6(output) => output.to_json(),
+            CommandOutput::SameSubnet(check) => Json::object([
+                ("ip1", Json::string(check.ip1)),
+                ("ip2", Json::string(check.ip2)),
+                ("mask1", Json::string(check.mask1)),
+                ("mask2", check.mask2.map(|mask| mask.to_string()).into()),
+                ("same_subnet", check.same_subnet.into()),
+            ]),
+            CommandOutput::CheckIp(ip) => Json::object([("ip", ip.as_str().into()), ("valid", true.into())]),
+            CommandOutput::CheckMask(check) => Json::object([
+                ("mask", check.mask.as_str().into()),
+                ("valid", true.into()),
+                ("notation", notation_name(&check.notation).into()),
+                ("prefix", check.prefix.into()),
+                ("netmask", Json::string(check.netmask)),
+                ("wildcard", Json::string(check.wildcard)),
+            ]),
+            CommandOutput::Lookup { routes_loaded, matches } => {
+                let results = matches.iter().map(|route_match| {
+                    let (ip, route) = match route_match {
+                        RouteMatch::V4(ip, route) => (Json::string(ip), route.as_ref().map(ToJson::to_json)),
+                        RouteMatch::V6(ip, route) => (Json::string(ip), route.as_ref().map(ToJson::to_json)),
+                    };
+                    Json::object([("ip", ip), ("route", route.unwrap_or(Json::Null))])
+                });
+                Json::object([
+                    ("routes_loaded", (*routes_loaded).into()),
+                    ("results", Json::array(results)),
+                ])
+            },
+            CommandOutput::Classify(results) => {
+                let results = results.iter().map(|result| Json::object([
+                    ("input", result.input.as_str().into()),
+                    ("classification", result.classification.to_json()),
+                ]));
+                Json::object([("results", Json::array(results))])
+            },
+            CommandOutput::Convert(results) => {
+                let results = results.iter().map(|conversion| {
+                    let ip = conversion.ip;
+                    let mask = conversion.mask.map(|(kind, prefix)| Json::object([
+                        ("kind", kind.into()),
+                        ("prefix", prefix.into()),
+                    ]));
+                    Json::object([
+                        ("input", conversion.input.as_str().into()),
+                        ("dotted", Json::string(ip)),
+                        ("integer", to_integer(ip).into()),
+                        ("hex", to_hex(ip).into()),
+                        ("binary", to_binary(ip).into()),
+                        ("mask", mask.unwrap_or(Json::Null)),
+                    ])
+                });
+                Json::object([("results", Json::array(results))])
+            },
+        };
+
+        if let Json::Object(fields) = &mut body {
+            fields.insert(0, ("command".to_string(), self.name().into()));
+        }
+        body
+    }
+}
+
+impl<A: IpFamily> ToJson for FamilyOutput<A> {
+    fn to_json(&self) -> Json {
+        match self {
+            FamilyOutput::Subnets(listing) => Json::object([
+                ("base", listing.base.to_json()),
+                ("prefix", listing.prefix.into()),
+                ("total_subnets", listing.total_subnets.into()),
+                ("displayed", listing.subnets.len().into()),
+                ("page", (if listing.total_pages > 0 { listing.page + 1 } else { 0 }).into()),
+                ("total_pages", listing.total_pages.into()),
+                ("subnets", Json::array(&listing.subnets)),
+                ("output_file", listing.output_file.clone().into()),
+            ]),
+            FamilyOutput::GetSubnet(selection) => Json::object([
+                ("requested_index", selection.requested_index.into()),
+                ("index", selection.index().into()),
+                ("total_subnets", selection.total_subnets.into()),
+                ("clamped", selection.clamped().into()),
+                ("subnet", selection.subnet.to_json()),
+                ("output_file", selection.output_file.clone().into()),
+            ]),
+            FamilyOutput::FindRange { cidr, size, start, end } => Json::object([
+                ("cidr", cidr.as_str().into()),
+                ("size", (*size).into()),
+                ("start", Json::string(start)),
+                ("end", Json::string(end)),
+            ]),
+            FamilyOutput::Display { subnet, verbose, output_file } => {
+                let mut json = Json::object([("subnet", subnet.to_json())]);
+                if *verbose {
+                    let last_label = if A::HAS_BROADCAST { "broadcast" } else { "last" };
+                    let binary = [("network", subnet.network), ("mask", subnet.mask), (last_label, subnet.broadcast)]
+                        .into_iter()
+                        .map(|(label, ip)| {
+                            let (network, host) = split_binary(ip, subnet.prefix);
+                            (label, Json::object([("network", network.into()), ("host", host.into())]))
+                        });
+                    json.push("binary", Json::object(binary));
+                }
+                if let Some(path) = output_file {
+                    json.push("output_file", path.as_str());
+                }
+                json
+            },
+            FamilyOutput::RangeToCidr { range, subnets, output_file } => {
+                let cidrs: Vec<String> = subnets.iter().map(ToString::to_string).collect();
+                let mut json = Json::object([("range", range.to_json()), ("cidrs", cidrs.into())]);
+                if let Some(path) = output_file {
+                    json.push("output_file", path.as_str());
+                }
+                json
+            },
+            FamilyOutput::Set { operation, outcome } => match outcome {
+                SetOutcome::Subset(is_subset) => Json::object([
+                    ("operation", Json::string(operation)),
+                    ("is_subset", (*is_subset).into()),
+                ]),
+                SetOutcome::Set(result) => {
+                    let cidrs: Vec<String> = result.to_subnets().iter().map(ToString::to_string).collect();
+                    Json::object([
+                        ("operation", Json::string(operation)),
+                        ("addresses", result.len().into()),
+                        ("cidrs", cidrs.into()),
+                    ])
+                },
+            },
+            FamilyOutput::Aggregate { input_count, summaries, .. } => {
+                let extra: AddressCount = summaries.iter().map(|s| s.extra_addresses).sum();
+                Json::object([
+                    ("input_count", (*input_count).into()),
+                    ("output_count", summaries.len().into()),
+                    ("reduction_percent", reduction_ratio(*input_count, summaries.len()).into()),
+                    ("extra_addresses", extra.into()),
+                    ("prefixes", Json::array(summaries)),
+                ])
+            },
+            FamilyOutput::Supernet { supernet, inputs, extra_addresses } => Json::object([
+                ("supernet", supernet.to_json()),
+                ("inputs", (*inputs).into()),
+                ("extra_addresses", (*extra_addresses).into()),
+            ]),
+            FamilyOutput::Exclude { base, remaining, output_file } => {
+                let addresses = remaining
+                    .iter()
+                    .map(Subnet::address_total)
+                    .sum::<AddressCount>();
+                Json::object([
+                    ("base", Json::string(base)),
+                    ("addresses", addresses.into()),
+                    ("remaining", Json::array(remaining)),
+                    ("output_file", output_file.clone().into()),
+                ])
+            },
+            FamilyOutput::Vlsm { parent, allocations } => {
+                let (wasted, free) = vlsm_totals(parent, allocations);
+                Json::object([
+                    ("parent", Json::string(parent)),
+                    ("allocations", Json::array(allocations)),
+                    ("wasted_hosts", wasted.into()),
+                    ("free_addresses", free.into()),
+                ])
+            },
+            FamilyOutput::Arithmetic { operation, operands, result } => Json::object([
+                ("operation", Json::string(operation)),
+                ("operands", operands.clone().into()),
+                ("result", result.to_json()),
+            ]),
+            FamilyOutput::ReverseDns(report) => {
+                let owners = report
+                    .ptr_owners
+                    .iter()
+                    .map(|(ip, owner)| Json::object([("ip", Json::string(ip)), ("owner", owner.as_str().into())]));
+                Json::object([
+                    ("cidr", Json::string(report.subnet)),
+                    ("zones", report.zones.clone().into()),
+                    ("classless_zone", report.classless_zone.clone().into()),
+                    ("cname_records", Json::array(&report.cname_records)),
+                    ("ptr_owners", Json::array(owners)),
+                    ("total_addresses", report.total_addresses.into()),
+                    ("truncated", (report.total_addresses > AddressCount::from(report.ptr_owners.len() as u128)).into()),
+                ])
+            },
+            FamilyOutput::PtrZone { zone, output_file } => {
+                let mut json = zone.to_json();
+                if output_file.is_none() {
+                    json.push("zone_file", zone.to_string());
+                }
+                json.push("output_file", output_file.clone());
+                json
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::libs::calc_ip::{execute_command, Command};
+    use crate::libs::export::OutputFile;
+
+    fn run(command: Command) -> CommandOutput {
+        execute_command(command).unwrap()
+    }
+
+    #[test]
+    fn test_plain_and_colored_text() {
+        let output = run(Command::Display { cidr: "10.0.0.0/30".to_string(), verbose: false, output_file: None });
+        let plain = render(&output, Format::Text);
+        assert!(plain.starts_with("Network: 10.0.0.0\nMask: 255.255.255.252\n"));
+        assert!(!plain.contains('\x1b'));
+        assert!(render(&output, Format::Colored).contains("\x1b[1;34mHosts:\x1b[0m 2"));
+
+        let output = run(Command::SameSubnet {
+            ip1: Ipv4Addr::new(10, 0, 0, 1).into(),
+            ip2: Ipv4Addr::new(10, 0, 1, 1).into(),
+            mask1: Ipv4Addr::new(255, 255, 255, 0).into(),
+            mask2: None,
+        });
+        assert_eq!(render(&output, Format::Text), "IP addresses 10.0.0.1 and 10.0.1.1 are not in the same subnet");
+    }
+
+    #[test]
+    fn test_json_and_csv() {
+        let output = run(Command::Subnets {
+            cidr: "10.0.0.0/24".to_string(),
+            prefix: 26,
+            filter: None,
+            page: Some(1),
+            output_file: None,
+        });
+        let json = output.to_json();
+        assert_eq!(json.get("command"), Some(&Json::String("subnets".to_string())));
+        assert_eq!(json.get("displayed"), Some(&Json::Number("4".to_string())));
+
+        let output = run(Command::Subnets {
+            cidr: "10.0.0.0/24".to_string(),
+            prefix: 28,
+            filter: None,
+            page: Some(1),
+            output_file: None,
+        });
+        let csv = render(&output, Format::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("index,network,prefix,mask,wildcard,broadcast,first,last,hosts"));
+        assert!(lines.next().unwrap().starts_with("4,10.0.0.64,28,"));
+
+        let output = run(Command::CheckIP { ip: "10.0.0.1".to_string() });
+        assert_eq!(render(&output, Format::Tsv), "index\tnetwork\tprefix\tmask\twildcard\tbroadcast\tfirst\tlast\thosts\n");
+    }
+
+    #[test]
+    fn test_export_lists_every_subnet_without_writing() {
+        let path = std::env::temp_dir().join(format!("ip_calculator_export_{}.csv", std::process::id()));
+        let output = run(Command::Subnets {
+            cidr: "10.0.0.0/24".to_string(),
+            prefix: 28,
+            filter: Some(6),
+            page: Some(1),
+            output_file: Some(OutputFile::new(path.to_str().unwrap(), None).unwrap()),
+        });
+        assert!(!path.exists());
+        assert!(render(&output, Format::Text).ends_with(&format!("Output written to {}", path.display())));
+
+        let mut csv = Vec::new();
+        export(&mut csv, &output, ExportFormat::Csv, &[Column::Index, Column::Network]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 7);
+        assert!(csv.ends_with("5,10.0.0.80\n"));
+    }
+
+    struct CappedWriter {
+        written: usize,
+    }
+
+    impl Write for CappedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written + buf.len() > 64 * 1024 {
+                return Err(io::Error::other("writer is full"));
+            }
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_export_streams_without_building_the_listing() {
+        let output = run(Command::Subnets {
+            cidr: "2001:db8::/32".to_string(),
+            prefix: 128,
+            filter: None,
+            page: Some(1),
+            output_file: None,
+        });
+        let mut writer = CappedWriter { written: 0 };
+        let error = export(&mut writer, &output, ExportFormat::Csv, &[Column::Network]).unwrap_err();
+        assert_eq!(error.to_string(), "writer is full");
+        assert!(writer.written > 60 * 1024);
+    }
+
+    #[test]
+    fn test_render_error() {
+        let error = execute_command(Command::CheckMask { mask: "255.0.255.0".to_string(), wildcard: false }).unwrap_err();
+        let text = render_error(&error, Style::Plain);
+        assert!(text.starts_with("Subnet mask 255.0.255.0 is not contiguous\nBinary: 11111111.00000000.11111111.00000000\n"));
+        assert!(text.ends_with(&format!("\nError: {}", error)));
+
+        let error = IpCalculatorError::InvalidIP("1.2.3".to_string());
+        assert_eq!(render_error(&error, Style::Plain), "Error: Invalid IP: 1.2.3");
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::libs::calc_ip::{IpFamily, Subnet};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::subnet_iter::HostIter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CnameRecord {
+    pub owner: String,
+    pub target: String,
+}
+
+impl fmt::Display for CnameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}. IN CNAME {}.", self.owner, self.target)
+    }
+}
+
+impl ToJson for CnameRecord {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("owner", self.owner.as_str().into()),
+            ("target", self.target.as_str().into()),
+        ])
+    }
+}
+
+fn label_bits<A: IpFamily>() -> u8 {
+    if A::BITS == 32 { 8 } else { 4 }
+}
+
+fn arpa_suffix<A: IpFamily>() -> &'static str {
+    if A::BITS == 32 { "in-addr.arpa" } else { "ip6.arpa" }
+}
+
+fn reversed_labels<A: IpFamily>(bits: u128, count: u8) -> Vec<String> {
+    let width = label_bits::<A>();
+    (0..count)
+        .rev()
+        .map(|i| {
+            let label = (bits >> (A::BITS - width * (i + 1))) & ((1 << width) - 1);
+            if A::BITS == 32 { label.to_string() } else { format!("{:x}", label) }
+        })
+        .collect()
+}
+
+fn arpa_name<A: IpFamily>(bits: u128, labels: u8) -> String {
+    let mut parts = reversed_labels::<A>(bits, labels);
+    parts.push(arpa_suffix::<A>().to_string());
+    parts.join(".")
+}
+
+pub fn ptr_name<A: IpFamily>(ip: A) -> String {
+    arpa_name::<A>(ip.to_bits(), A::BITS / label_bits::<A>())
+}
+
+pub fn is_classless<A: IpFamily>(subnet: &Subnet<A>) -> bool {
+    A::BITS == 32 && subnet.prefix > 24 && subnet.prefix < 32
+}
+
+pub fn reverse_zones<A: IpFamily>(subnet: &Subnet<A>) -> Vec<String> {
+    let width = label_bits::<A>();
+    if is_classless(subnet) {
+        return vec![arpa_name::<A>(subnet.network.to_bits(), 3)];
+    }
+
+    let zone_prefix = subnet.prefix.div_ceil(width) * width;
+    let zone_size = A::host_bits(zone_prefix).saturating_add(1);
+    let count = 1u128 << (zone_prefix - subnet.prefix);
+    (0..count)
+        .map(|i| arpa_name::<A>(subnet.network.to_bits() + i * zone_size, zone_prefix / width))
+        .collect()
+}
+
+pub fn classless_zone<A: IpFamily>(subnet: &Subnet<A>) -> Option<String> {
+    is_classless(subnet).then(|| {
+        let network_octet = subnet.network.to_bits() & 0xFF;
+        format!("{}/{}.{}", network_octet, subnet.prefix, reverse_zones(subnet)[0])
+    })
+}
+
+pub fn ptr_owner<A: IpFamily>(subnet: &Subnet<A>, ip: A) -> String {
+    match classless_zone(subnet) {
+        Some(zone) => format!("{}.{}", ip.to_bits() & 0xFF, zone),
+        None => ptr_name(ip),
+    }
+}
+
+pub fn classless_delegation<A: IpFamily>(subnet: &Subnet<A>) -> Vec<CnameRecord> {
+    if !is_classless(subnet) {
+        return Vec::new();
+    }
+    HostIter::new(subnet.network, subnet.broadcast)
+        .map(|ip| CnameRecord {
+            owner: ptr_name(ip),
+            target: ptr_owner(subnet, ip),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_ptr_name() {
+        assert_eq!(ptr_name(Ipv4Addr::new(192, 0, 2, 65)), "65.2.0.192.in-addr.arpa");
+        assert_eq!(
+            ptr_name("2001:db8::1".parse::<Ipv6Addr>().unwrap()),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn test_octet_boundary_zones() {
+        assert_eq!(reverse_zones(&"10.0.0.0/8".parse::<Subnet>().unwrap()), vec!["10.in-addr.arpa"]);
+        assert_eq!(reverse_zones(&"192.168.1.0/24".parse::<Subnet>().unwrap()), vec!["1.168.192.in-addr.arpa"]);
+        assert_eq!(
+            reverse_zones(&"192.168.4.0/22".parse::<Subnet>().unwrap()),
+            vec![
+                "4.168.192.in-addr.arpa",
+                "5.168.192.in-addr.arpa",
+                "6.168.192.in-addr.arpa",
+                "7.168.192.in-addr.arpa",
+            ]
+        );
+        assert_eq!(reverse_zones(&"0.0.0.0/0".parse::<Subnet>().unwrap()), vec!["in-addr.arpa"]);
+        assert_eq!(reverse_zones(&"192.0.2.7/32".parse::<Subnet>().unwrap()), vec!["7.2.0.192.in-addr.arpa"]);
+
+        let v6: Subnet<Ipv6Addr> = "2001:db8::/30".parse().unwrap();
+        let zones = reverse_zones(&v6);
+        assert_eq!(zones.len(), 4);
+        assert_eq!(zones[0], "8.b.d.0.1.0.0.2.ip6.arpa");
+        assert_eq!(zones[3], "b.b.d.0.1.0.0.2.ip6.arpa");
+    }
+
+    #[test]
+    fn test_classless_delegation() {
+        let net = "192.0.2.64/26".parse::<Subnet>().unwrap();
+        assert!(is_classless(&net));
+        assert_eq!(reverse_zones(&net), vec!["2.0.192.in-addr.arpa"]);
+        assert_eq!(classless_zone(&net).unwrap(), "64/26.2.0.192.in-addr.arpa");
+        assert_eq!(ptr_owner(&net, Ipv4Addr::new(192, 0, 2, 65)), "65.64/26.2.0.192.in-addr.arpa");
+
+        let records = classless_delegation(&net);
+        assert_eq!(records.len(), 64);
+        assert_eq!(
+            records[1].to_string(),
+            "65.2.0.192.in-addr.arpa. IN CNAME 65.64/26.2.0.192.in-addr.arpa."
+        );
+
+        assert!(classless_zone(&"192.0.2.0/24".parse::<Subnet>().unwrap()).is_none());
+        assert!(classless_delegation(&"192.0.2.0/24".parse::<Subnet>().unwrap()).is_empty());
+    }
+}
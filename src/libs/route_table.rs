@@ -0,0 +1,203 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{is_ipv6, IpCalculatorError, IpFamily, Subnet};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::prefix_trie::PrefixTrie;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route<A: IpFamily = Ipv4Addr> {
+    pub prefix: Subnet<A>,
+    pub next_hop: String,
+    pub metric: u32,
+}
+
+impl<A: IpFamily> fmt::Display for Route<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} via {} metric {}", self.prefix, self.next_hop, self.metric)
+    }
+}
+
+impl<A: IpFamily> ToJson for Route<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("prefix", Json::string(self.prefix)),
+            ("next_hop", self.next_hop.as_str().into()),
+            ("metric", self.metric.into()),
+        ])
+    }
+}
+
+impl<A: IpFamily> FromStr for Route<A> {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = input.split_whitespace().collect();
+        let (prefix, next_hop, metric) = match fields.as_slice() {
+            [prefix, next_hop] => (prefix, next_hop, None),
+            [prefix, next_hop, metric] => (prefix, next_hop, Some(metric)),
+            _ => return Err(IpCalculatorError::ArgumentsError(format!(
+                "Invalid route '{}'! Expected: <CIDR> <next-hop> [metric]",
+                input.trim()
+            ))),
+        };
+
+        let metric = match metric {
+            Some(metric) => metric.parse::<u32>().map_err(|_| IpCalculatorError::ArgumentsError(
+                format!("Invalid route metric '{}'", metric)
+            ))?,
+            None => 0,
+        };
+
+        Ok(Route {
+            prefix: prefix.parse()?,
+            next_hop: next_hop.to_string(),
+            metric,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable<A: IpFamily = Ipv4Addr> {
+    routes: PrefixTrie<A, Vec<Route<A>>>,
+    len: usize,
+}
+
+impl<A: IpFamily> RouteTable<A> {
+    pub fn new() -> Self {
+        RouteTable { routes: PrefixTrie::new(), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, route: Route<A>) {
+        let routes = self.routes.get_or_insert_with(route.prefix, Vec::new);
+        let index = routes.partition_point(|existing| existing.metric <= route.metric);
+        routes.insert(index, route);
+        self.len += 1;
+    }
+
+    pub fn lookup(&self, ip: A) -> Option<&Route<A>> {
+        self.routes
+            .longest_match(ip)
+            .and_then(|(_, routes)| routes.first())
+    }
+
+    pub fn candidates(&self, ip: A) -> Vec<&Route<A>> {
+        self.routes
+            .matches(ip)
+            .into_iter()
+            .rev()
+            .flat_map(|(_, routes)| routes.iter())
+            .collect()
+    }
+
+    pub fn parse(input: &str) -> Result<Self, IpCalculatorError> {
+        let mut table = RouteTable::new();
+        for (number, line, ipv6) in route_lines(input) {
+            if ipv6 == (A::BITS == 128) {
+                table.insert(parse_route(number, line)?);
+            }
+        }
+        Ok(table)
+    }
+}
+
+pub fn parse_routes(input: &str) -> Result<(RouteTable<Ipv4Addr>, RouteTable<Ipv6Addr>), IpCalculatorError> {
+    let mut v4_routes = RouteTable::new();
+    let mut v6_routes = RouteTable::new();
+    for (number, line, ipv6) in route_lines(input) {
+        if ipv6 {
+            v6_routes.insert(parse_route(number, line)?);
+        } else {
+            v4_routes.insert(parse_route(number, line)?);
+        }
+    }
+    Ok((v4_routes, v6_routes))
+}
+
+fn route_lines(input: &str) -> impl Iterator<Item = (usize, &str, bool)> {
+    input.lines().enumerate().filter_map(|(number, line)| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let prefix = line.split_whitespace().next()?;
+        Some((number + 1, line, is_ipv6(prefix)))
+    })
+}
+
+fn parse_route<A: IpFamily>(number: usize, line: &str) -> Result<Route<A>, IpCalculatorError> {
+    line.parse().map_err(|e| IpCalculatorError::InputError(
+        format!("Invalid route on line {}: {}", number, e)
+    ))
+}
+
+impl<A: IpFamily> FromIterator<Route<A>> for RouteTable<A> {
+    fn from_iter<I: IntoIterator<Item = Route<A>>>(iter: I) -> Self {
+        let mut table = RouteTable::new();
+        for route in iter {
+            table.insert(route);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "\
+# destination     next-hop      metric
+0.0.0.0/0         192.0.2.1     100
+10.0.0.0/8        10.255.0.1
+10.1.0.0/16       10.255.0.2    20
+10.1.0.0/16       10.255.0.3    10
+10.1.2.0/24       eth1          5
+2001:db8::/32     fe80::1
+";
+
+    fn lookup(table: &RouteTable, ip: &str) -> Option<String> {
+        table.lookup(ip.parse().unwrap()).map(|route| route.to_string())
+    }
+
+    #[test]
+    fn test_parse_route_table() {
+        let table = RouteTable::<Ipv4Addr>::parse(TABLE).unwrap();
+        assert_eq!(table.len(), 5);
+
+        let err = RouteTable::<Ipv4Addr>::parse("10.0.0.0/8 10.0.0.1\n10.0.0.0/33 10.0.0.1").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!(RouteTable::<Ipv4Addr>::parse("10.0.0.0/8").is_err());
+        assert!(RouteTable::<Ipv4Addr>::parse("10.0.0.0/8 10.0.0.1 low").is_err());
+    }
+
+    #[test]
+    fn test_longest_prefix_match_with_metric_ties() {
+        let table = RouteTable::parse(TABLE).unwrap();
+        assert_eq!(lookup(&table, "10.1.2.3").unwrap(), "10.1.2.0/24 via eth1 metric 5");
+        assert_eq!(lookup(&table, "10.1.9.9").unwrap(), "10.1.0.0/16 via 10.255.0.3 metric 10");
+        assert_eq!(lookup(&table, "10.9.9.9").unwrap(), "10.0.0.0/8 via 10.255.0.1 metric 0");
+        assert_eq!(lookup(&table, "8.8.8.8").unwrap(), "0.0.0.0/0 via 192.0.2.1 metric 100");
+        assert_eq!(table.candidates("10.1.2.3".parse().unwrap()).len(), 5);
+
+        let no_default = RouteTable::<Ipv4Addr>::parse("10.0.0.0/8 10.255.0.1").unwrap();
+        assert!(lookup(&no_default, "8.8.8.8").is_none());
+    }
+
+    #[test]
+    fn test_ipv6_routes() {
+        let table = RouteTable::<Ipv6Addr>::parse(TABLE).unwrap();
+        assert_eq!(table.len(), 1);
+
+        let (v4_routes, v6_routes) = parse_routes(TABLE).unwrap();
+        assert_eq!((v4_routes.len(), v6_routes.len()), (5, 1));
+        assert!(parse_routes("2001:db8::/32 fe80::1\n10.0.0.0/33 10.0.0.1").unwrap_err().to_string().contains("line 2"));
+        let route = table.lookup("2001:db8::42".parse().unwrap()).unwrap();
+        assert_eq!(route.next_hop, "fe80::1");
+    }
+}
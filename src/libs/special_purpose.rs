@@ -0,0 +1,319 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::libs::calc_ip::{IpFamily, Subnet};
+use crate::libs::json::{Json, ToJson};
+use AddressCategory::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressCategory {
+    Private,
+    SharedAddressSpace,
+    Loopback,
+    LinkLocal,
+    Multicast,
+    Documentation,
+    Benchmarking,
+    Reserved,
+    GlobalUnicast,
+    Mixed,
+}
+
+impl fmt::Display for AddressCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AddressCategory::Private => "Private",
+            AddressCategory::SharedAddressSpace => "Shared Address Space",
+            AddressCategory::Loopback => "Loopback",
+            AddressCategory::LinkLocal => "Link-Local",
+            AddressCategory::Multicast => "Multicast",
+            AddressCategory::Documentation => "Documentation",
+            AddressCategory::Benchmarking => "Benchmarking",
+            AddressCategory::Reserved => "Reserved",
+            AddressCategory::GlobalUnicast => "Global Unicast",
+            AddressCategory::Mixed => "Mixed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Classification {
+    pub category: AddressCategory,
+    pub name: &'static str,
+    pub rfc: &'static str,
+    pub forwardable: bool,
+    pub globally_reachable: bool,
+}
+
+impl Classification {
+    const fn new(
+        category: AddressCategory,
+        name: &'static str,
+        rfc: &'static str,
+        forwardable: bool,
+        globally_reachable: bool,
+    ) -> Self {
+        Classification { category, name, rfc, forwardable, globally_reachable }
+    }
+
+    fn known(&self, flag: bool) -> Option<bool> {
+        (self.category != Mixed).then_some(flag)
+    }
+
+    fn flag_text(flag: Option<bool>) -> &'static str {
+        match flag {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "varies",
+        }
+    }
+
+    pub fn forwardable_text(&self) -> &'static str {
+        Self::flag_text(self.known(self.forwardable))
+    }
+
+    pub fn globally_reachable_text(&self) -> &'static str {
+        Self::flag_text(self.known(self.globally_reachable))
+    }
+
+    pub fn flags(&self) -> String {
+        format!(
+            "Forwardable: {} | Globally reachable: {}",
+            self.forwardable_text(),
+            self.globally_reachable_text()
+        )
+    }
+}
+
+impl ToJson for Classification {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("category", Json::string(self.category)),
+            ("name", self.name.into()),
+            ("rfc", self.rfc.into()),
+            ("forwardable", self.known(self.forwardable).into()),
+            ("globally_reachable", self.known(self.globally_reachable).into()),
+        ])
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let category = self.category.to_string();
+        match (self.name, self.rfc) {
+            (_, "") => write!(f, "{}", category),
+            (name, rfc) if name == category => write!(f, "{} ({})", category, rfc),
+            (name, rfc) => write!(f, "{} ({}, {})", category, name, rfc),
+        }
+    }
+}
+
+pub const GLOBAL_UNICAST: Classification = Classification::new(GlobalUnicast, "Global Unicast", "", true, true);
+
+pub const MIXED: Classification =
+    Classification::new(Mixed, "Spans several special-purpose ranges", "", false, false);
+
+const IPV4_REGISTRY: &[(&str, Classification)] = &[
+    ("0.0.0.0/8", Classification::new(Reserved, "This network", "RFC 791", false, false)),
+    ("0.0.0.0/32", Classification::new(Reserved, "This host on this network", "RFC 1122", false, false)),
+    ("10.0.0.0/8", Classification::new(Private, "Private-Use", "RFC 1918", true, false)),
+    ("100.64.0.0/10", Classification::new(SharedAddressSpace, "Shared Address Space", "RFC 6598", true, false)),
+    ("127.0.0.0/8", Classification::new(Loopback, "Loopback", "RFC 1122", false, false)),
+    ("169.254.0.0/16", Classification::new(LinkLocal, "Link Local", "RFC 3927", false, false)),
+    ("172.16.0.0/12", Classification::new(Private, "Private-Use", "RFC 1918", true, false)),
+    ("192.0.0.0/24", Classification::new(Reserved, "IETF Protocol Assignments", "RFC 6890", false, false)),
+    ("192.0.0.0/29", Classification::new(Reserved, "IPv4 Service Continuity Prefix", "RFC 7335", true, false)),
+    ("192.0.0.8/32", Classification::new(Reserved, "IPv4 dummy address", "RFC 7600", false, false)),
+    ("192.0.0.9/32", Classification::new(Reserved, "Port Control Protocol Anycast", "RFC 7723", true, true)),
+    ("192.0.0.10/32", Classification::new(Reserved, "Traversal Using Relays around NAT Anycast", "RFC 8155", true, true)),
+    ("192.0.0.170/32", Classification::new(Reserved, "NAT64/DNS64 Discovery", "RFC 8880", false, false)),
+    ("192.0.0.171/32", Classification::new(Reserved, "NAT64/DNS64 Discovery", "RFC 8880", false, false)),
+    ("192.0.2.0/24", Classification::new(Documentation, "TEST-NET-1", "RFC 5737", false, false)),
+    ("192.88.99.0/24", Classification::new(Reserved, "Deprecated 6to4 Relay Anycast", "RFC 7526", true, true)),
+    ("192.168.0.0/16", Classification::new(Private, "Private-Use", "RFC 1918", true, false)),
+    ("198.18.0.0/15", Classification::new(Benchmarking, "Benchmarking", "RFC 2544", true, false)),
+    ("198.51.100.0/24", Classification::new(Documentation, "TEST-NET-2", "RFC 5737", false, false)),
+    ("203.0.113.0/24", Classification::new(Documentation, "TEST-NET-3", "RFC 5737", false, false)),
+    ("224.0.0.0/4", Classification::new(Multicast, "Multicast", "RFC 5771", true, false)),
+    ("240.0.0.0/4", Classification::new(Reserved, "Reserved", "RFC 1112", false, false)),
+    ("255.255.255.255/32", Classification::new(Reserved, "Limited Broadcast", "RFC 919", false, false)),
+];
+
+const IPV6_REGISTRY: &[(&str, Classification)] = &[
+    ("::/8", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("::/128", Classification::new(Reserved, "Unspecified Address", "RFC 4291", false, false)),
+    ("::1/128", Classification::new(Loopback, "Loopback Address", "RFC 4291", false, false)),
+    ("::ffff:0:0/96", Classification::new(Reserved, "IPv4-mapped Address", "RFC 4291", false, false)),
+    ("64:ff9b::/96", Classification::new(Reserved, "IPv4-IPv6 Translation", "RFC 6052", true, true)),
+    ("64:ff9b:1::/48", Classification::new(Reserved, "IPv4-IPv6 Translation", "RFC 8215", true, false)),
+    ("100::/8", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("100::/64", Classification::new(Reserved, "Discard-Only Address Block", "RFC 6666", true, false)),
+    ("200::/7", Classification::new(Reserved, "Reserved by IETF", "RFC 4048", false, false)),
+    ("400::/6", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("800::/5", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("1000::/4", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("2001::/23", Classification::new(Reserved, "IETF Protocol Assignments", "RFC 2928", false, false)),
+    ("2001::/32", Classification::new(Reserved, "TEREDO", "RFC 4380", true, false)),
+    ("2001:1::1/128", Classification::new(Reserved, "Port Control Protocol Anycast", "RFC 7723", true, true)),
+    ("2001:1::2/128", Classification::new(Reserved, "Traversal Using Relays around NAT Anycast", "RFC 8155", true, true)),
+    ("2001:2::/48", Classification::new(Benchmarking, "Benchmarking", "RFC 5180", true, false)),
+    ("2001:3::/32", Classification::new(Reserved, "AMT", "RFC 7450", true, true)),
+    ("2001:4:112::/48", Classification::new(Reserved, "AS112-v6", "RFC 7535", true, true)),
+    ("2001:20::/28", Classification::new(Reserved, "ORCHIDv2", "RFC 7343", true, true)),
+    ("2001:db8::/32", Classification::new(Documentation, "Documentation", "RFC 3849", false, false)),
+    ("2002::/16", Classification::new(Reserved, "6to4", "RFC 3056", true, false)),
+    ("3fff::/20", Classification::new(Documentation, "Documentation", "RFC 9637", false, false)),
+    ("4000::/3", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("6000::/3", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("8000::/3", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("a000::/3", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("c000::/3", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("e000::/4", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("f000::/5", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("f800::/6", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("fc00::/7", Classification::new(Private, "Unique-Local", "RFC 4193", true, false)),
+    ("fe00::/9", Classification::new(Reserved, "Reserved by IETF", "RFC 4291", false, false)),
+    ("fe80::/10", Classification::new(LinkLocal, "Link-Local Unicast", "RFC 4291", false, false)),
+    ("fec0::/10", Classification::new(Reserved, "Deprecated Site-Local", "RFC 3879", false, false)),
+    ("ff00::/8", Classification::new(Multicast, "Multicast", "RFC 4291", true, false)),
+];
+
+type ParsedRegistry = Vec<(u128, u8, &'static Classification)>;
+
+static IPV4_PARSED: OnceLock<ParsedRegistry> = OnceLock::new();
+static IPV6_PARSED: OnceLock<ParsedRegistry> = OnceLock::new();
+
+fn parse_registry<A: IpFamily>(table: &'static [(&'static str, Classification)]) -> ParsedRegistry {
+    table
+        .iter()
+        .map(|(cidr, classification)| {
+            let subnet: Subnet<A> = cidr.parse().unwrap_or_else(|_| panic!("invalid registry prefix {}", cidr));
+            (subnet.network.to_bits(), subnet.prefix, classification)
+        })
+        .collect()
+}
+
+pub fn registry<A: IpFamily>() -> impl Iterator<Item = (Subnet<A>, &'static Classification)> {
+    let parsed = if A::BITS == 32 {
+        IPV4_PARSED.get_or_init(|| parse_registry::<A>(IPV4_REGISTRY))
+    } else {
+        IPV6_PARSED.get_or_init(|| parse_registry::<A>(IPV6_REGISTRY))
+    };
+    parsed
+        .iter()
+        .map(|(network, prefix, classification)| (Subnet::from_bits(*network, *prefix), *classification))
+}
+
+pub fn classify<A: IpFamily>(subnet: &Subnet<A>) -> &'static Classification {
+    let mut overlaps = false;
+    let mut best: Option<(u8, &'static Classification)> = None;
+
+    for (prefix, classification) in registry::<A>() {
+        if prefix.contains_subnet(subnet) {
+            if best.is_none_or(|(length, _)| prefix.prefix > length) {
+                best = Some((prefix.prefix, classification));
+            }
+        } else if subnet.overlaps_with(&prefix) {
+            overlaps = true;
+        }
+    }
+
+    match best {
+        Some((_, classification)) => classification,
+        None if overlaps => &MIXED,
+        None => &GLOBAL_UNICAST,
+    }
+}
+
+pub fn classify_ip<A: IpFamily>(ip: A) -> &'static Classification {
+    classify(&Subnet::<A>::from_bits(ip.to_bits(), A::BITS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn v4(input: &str) -> &'static Classification {
+        classify::<Ipv4Addr>(&input.parse().unwrap())
+    }
+
+    fn v6(input: &str) -> &'static Classification {
+        classify::<Ipv6Addr>(&input.parse().unwrap())
+    }
+
+    #[test]
+    fn test_registry_parses() {
+        assert_eq!(registry::<Ipv4Addr>().count(), IPV4_REGISTRY.len());
+        assert_eq!(registry::<Ipv6Addr>().count(), IPV6_REGISTRY.len());
+    }
+
+    #[test]
+    fn test_classify_ipv4() {
+        assert_eq!(v4("10.20.0.0/16").category, Private);
+        assert_eq!(v4("172.31.255.0/24").category, Private);
+        assert_eq!(v4("100.64.1.0/24").category, SharedAddressSpace);
+        assert_eq!(v4("127.0.0.1/32").category, Loopback);
+        assert_eq!(v4("169.254.10.0/24").category, LinkLocal);
+        assert_eq!(v4("239.1.1.0/24").category, Multicast);
+        assert_eq!(v4("198.51.100.0/25").category, Documentation);
+        assert_eq!(v4("198.19.0.0/16").category, Benchmarking);
+        assert_eq!(v4("250.0.0.0/8").category, Reserved);
+        assert_eq!(v4("8.8.8.0/24"), &GLOBAL_UNICAST);
+        assert_eq!(v4("0.0.0.0/0"), &MIXED);
+        assert_eq!(v4("10.0.0.0/7"), &MIXED);
+        assert_eq!(MIXED.flags(), "Forwardable: varies | Globally reachable: varies");
+        assert_eq!(MIXED.to_json().get("forwardable"), Some(&Json::Null));
+        assert_eq!(v4("10.0.0.0/8").to_json().get("forwardable"), Some(&Json::Bool(true)));
+    }
+
+    #[test]
+    fn test_classify_flags_and_most_specific() {
+        let private = classify_ip(Ipv4Addr::new(192, 168, 1, 1));
+        assert!(private.forwardable);
+        assert!(!private.globally_reachable);
+        assert_eq!(private.to_string(), "Private (Private-Use, RFC 1918)");
+
+        assert_eq!(classify_ip(Ipv4Addr::UNSPECIFIED).name, "This host on this network");
+        assert_eq!(classify_ip(Ipv4Addr::new(0, 1, 2, 3)).name, "This network");
+        assert_eq!(classify_ip(Ipv4Addr::BROADCAST).name, "Limited Broadcast");
+        assert_eq!(GLOBAL_UNICAST.to_string(), "Global Unicast");
+        assert_eq!(GLOBAL_UNICAST.flags(), "Forwardable: yes | Globally reachable: yes");
+    }
+
+    #[test]
+    fn test_classify_ipv6() {
+        assert_eq!(classify_ip(Ipv6Addr::LOCALHOST).category, Loopback);
+        assert_eq!(v6("fd12:3456::/48").category, Private);
+        assert_eq!(v6("fe80::/64").category, LinkLocal);
+        assert_eq!(v6("ff02::1/128").category, Multicast);
+        assert_eq!(v6("2001:db8:1::/48").category, Documentation);
+        assert_eq!(v6("2001:4860::/32"), &GLOBAL_UNICAST);
+        assert_eq!(v6("::ffff:10.0.0.1/128").name, "IPv4-mapped Address");
+        assert_eq!(v6("4000::/16").category, Reserved);
+        assert_eq!(v6("2001:2::/48").to_string(), "Benchmarking (RFC 5180)");
+        assert_eq!(v6("2000::/3"), &MIXED);
+    }
+
+    #[test]
+    fn test_more_specific_entry_overrides_parent_flags() {
+        let parent = classify_ip(Ipv4Addr::new(192, 0, 0, 100));
+        assert_eq!(parent.name, "IETF Protocol Assignments");
+        assert!(!parent.globally_reachable);
+
+        let pcp = classify_ip(Ipv4Addr::new(192, 0, 0, 9));
+        assert_eq!(pcp.name, "Port Control Protocol Anycast");
+        assert!(pcp.forwardable && pcp.globally_reachable);
+        assert!(classify_ip(Ipv4Addr::new(192, 0, 0, 10)).globally_reachable);
+        assert!(!classify_ip(Ipv4Addr::new(192, 0, 0, 170)).forwardable);
+
+        assert!(!v6("2001:5::/48").globally_reachable);
+        for reachable in ["2001:1::1/128", "2001:1::2/128", "2001:3:1::/48", "2001:4:112::/48", "2001:2f::/32"] {
+            let classification = v6(reachable);
+            assert!(classification.globally_reachable, "{} should be globally reachable", reachable);
+            assert_eq!(classification.category, Reserved);
+        }
+        assert_eq!(v6("2001:20::/28").to_string(), "Reserved (ORCHIDv2, RFC 7343)");
+    }
+}
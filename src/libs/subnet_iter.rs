@@ -0,0 +1,273 @@
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::net::Ipv4Addr;
+
+use crate::libs::calc_ip::{IpFamily, Subnet};
+
+pub struct SubnetIter<A: IpFamily = Ipv4Addr> {
+    base: u128,
+    prefix: u8,
+    front: u128,
+    back: u128,
+    _family: PhantomData<A>,
+}
+
+impl<A: IpFamily> SubnetIter<A> {
+    pub(crate) fn new(base: &Subnet<A>, prefix: u8, count: u128) -> Self {
+        SubnetIter {
+            base: base.network.to_bits(),
+            prefix,
+            front: 0,
+            back: count,
+            _family: PhantomData,
+        }
+    }
+
+    pub fn remaining(&self) -> u128 {
+        self.back - self.front
+    }
+
+    pub fn seek(&mut self, index: u128) -> &mut Self {
+        self.front = index.min(self.back);
+        self
+    }
+
+    fn subnet_at(&self, index: u128) -> Subnet<A> {
+        let increment = A::host_bits(self.prefix) + 1;
+        Subnet::from_bits(self.base + index * increment, self.prefix)
+    }
+}
+
+impl<A: IpFamily> Iterator for SubnetIter<A> {
+    type Item = Subnet<A>;
+
+    fn next(&mut self) -> Option<Subnet<A>> {
+        if self.front >= self.back {
+            return None;
+        }
+        let subnet = self.subnet_at(self.front);
+        self.front += 1;
+        Some(subnet)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Subnet<A>> {
+        self.front = self.front.saturating_add(n as u128).min(self.back);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint(self.remaining())
+    }
+}
+
+impl<A: IpFamily> DoubleEndedIterator for SubnetIter<A> {
+    fn next_back(&mut self) -> Option<Subnet<A>> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.subnet_at(self.back))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Subnet<A>> {
+        self.back = self.back.saturating_sub(n as u128).max(self.front);
+        self.next_back()
+    }
+}
+
+impl ExactSizeIterator for SubnetIter<Ipv4Addr> {}
+
+impl<A: IpFamily> FusedIterator for SubnetIter<A> {}
+
+pub struct HostIter<A: IpFamily = Ipv4Addr> {
+    first: u128,
+    front: u128,
+    back: u128,
+    done: bool,
+    _family: PhantomData<A>,
+}
+
+impl<A: IpFamily> HostIter<A> {
+    pub(crate) fn new(first: A, last: A) -> Self {
+        HostIter {
+            first: first.to_bits(),
+            front: first.to_bits(),
+            back: last.to_bits(),
+            done: first > last,
+            _family: PhantomData,
+        }
+    }
+
+    pub fn remaining(&self) -> u128 {
+        if self.done {
+            0
+        } else {
+            (self.back - self.front).saturating_add(1)
+        }
+    }
+
+    pub fn seek(&mut self, index: u128) -> &mut Self {
+        match self.first.checked_add(index) {
+            Some(target) if target <= self.back => {
+                self.front = target;
+                self.done = false;
+            }
+            _ => self.done = true,
+        }
+        self
+    }
+}
+
+impl<A: IpFamily> Iterator for HostIter<A> {
+    type Item = A;
+
+    fn next(&mut self) -> Option<A> {
+        if self.done {
+            return None;
+        }
+        let host = self.front;
+        if self.front == self.back {
+            self.done = true;
+        } else {
+            self.front += 1;
+        }
+        Some(A::from_bits(host))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<A> {
+        if self.done {
+            return None;
+        }
+        match self.front.checked_add(n as u128) {
+            Some(target) if target <= self.back => {
+                self.front = target;
+                self.next()
+            }
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint(self.remaining())
+    }
+}
+
+impl<A: IpFamily> DoubleEndedIterator for HostIter<A> {
+    fn next_back(&mut self) -> Option<A> {
+        if self.done {
+            return None;
+        }
+        let host = self.back;
+        if self.front == self.back {
+            self.done = true;
+        } else {
+            self.back -= 1;
+        }
+        Some(A::from_bits(host))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<A> {
+        if self.done {
+            return None;
+        }
+        match self.back.checked_sub(n as u128) {
+            Some(target) if target >= self.front => {
+                self.back = target;
+                self.next_back()
+            }
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+impl ExactSizeIterator for HostIter<Ipv4Addr> {}
+
+impl<A: IpFamily> FusedIterator for HostIter<A> {}
+
+fn size_hint(remaining: u128) -> (usize, Option<usize>) {
+    match usize::try_from(remaining) {
+        Ok(len) => (len, Some(len)),
+        Err(_) => (usize::MAX, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_subnet_iter_forward_and_back() {
+        let base = Subnet::new(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap();
+        let mut iter = base.subnets(26).unwrap();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next().unwrap().network, Ipv4Addr::new(10, 0, 0, 0));
+        assert_eq!(iter.next_back().unwrap().network, Ipv4Addr::new(10, 0, 0, 192));
+        assert_eq!(iter.len(), 2);
+        let rest: Vec<_> = iter.map(|s| s.network).collect();
+        assert_eq!(rest, vec![Ipv4Addr::new(10, 0, 0, 64), Ipv4Addr::new(10, 0, 0, 128)]);
+    }
+
+    #[test]
+    fn test_subnet_iter_seek_and_nth() {
+        let base = Subnet::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap();
+        let mut iter = base.subnets(32).unwrap();
+        assert_eq!(iter.len(), 1 << 24);
+        iter.seek(65536);
+        assert_eq!(iter.next().unwrap().network, Ipv4Addr::new(10, 1, 0, 0));
+        assert_eq!(iter.nth(255).unwrap().network, Ipv4Addr::new(10, 1, 1, 0));
+        assert_eq!(iter.nth_back(0).unwrap().network, Ipv4Addr::new(10, 255, 255, 255));
+        iter.seek(u128::MAX);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_subnet_iter_ipv6() {
+        let base = Subnet::new(Ipv6Addr::from_str("2001:db8::").unwrap(), 32).unwrap();
+        let mut iter = base.subnets(64).unwrap();
+        assert_eq!(iter.remaining(), 1 << 32);
+        assert_eq!(
+            iter.next_back().unwrap().network,
+            Ipv6Addr::from_str("2001:db8:ffff:ffff::").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_host_iter() {
+        let subnet = Subnet::new(Ipv4Addr::new(192, 168, 1, 0), 30).unwrap();
+        let mut hosts = subnet.hosts();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts.next_back(), Some(Ipv4Addr::new(192, 168, 1, 2)));
+        assert_eq!(hosts.next(), Some(Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(hosts.next(), None);
+        assert_eq!(hosts.len(), 0);
+
+        let mut hosts = subnet.hosts();
+        hosts.seek(1);
+        assert_eq!(hosts.next(), Some(Ipv4Addr::new(192, 168, 1, 2)));
+        hosts.seek(2);
+        assert_eq!(hosts.next(), None);
+    }
+
+    #[test]
+    fn test_host_iter_large_subnets() {
+        let subnet = Subnet::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap();
+        let mut hosts = subnet.hosts();
+        assert_eq!(hosts.len(), (1 << 24) - 2);
+        assert_eq!(hosts.nth(255), Some(Ipv4Addr::new(10, 0, 1, 0)));
+        assert_eq!(hosts.nth_back(1), Some(Ipv4Addr::new(10, 255, 255, 253)));
+
+        let all = Subnet::new(Ipv6Addr::UNSPECIFIED, 0).unwrap();
+        let mut hosts = all.hosts();
+        assert_eq!(hosts.size_hint(), (usize::MAX, None));
+        assert_eq!(hosts.next_back(), Some(Ipv6Addr::from(u128::MAX)));
+        assert_eq!(hosts.remaining(), u128::MAX);
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use crate::libs::calc_ip::{IpCalculatorError, IpFamily, Subnet};
+use crate::libs::ip_set::list_items;
+use crate::libs::json::{Json, ToJson};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub name: String,
+    pub hosts: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocation<A: IpFamily = Ipv4Addr> {
+    pub name: String,
+    pub hosts: u128,
+    pub subnet: Subnet<A>,
+    pub wasted: u128,
+}
+
+impl Requirement {
+    pub fn prefix_for<A: IpFamily>(&self) -> Result<u8, IpCalculatorError> {
+        let longest = if A::HAS_BROADCAST { A::BITS - 2 } else { A::BITS };
+        (0..=longest)
+            .rev()
+            .find(|prefix| Into::<u128>::into(Subnet::<A>::from_bits(0, *prefix).num_hosts) >= self.hosts)
+            .ok_or_else(|| IpCalculatorError::SubnetError(format!(
+                "{} needs {} hosts, more than any IPv{} network can hold",
+                self.name, self.hosts, if A::BITS == 32 { 4 } else { 6 }
+            )))
+    }
+}
+
+impl<A: IpFamily> ToJson for Allocation<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("name", self.name.as_str().into()),
+            ("hosts", self.hosts.into()),
+            ("subnet", self.subnet.to_json()),
+            ("wasted", self.wasted.into()),
+        ])
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.hosts)
+    }
+}
+
+impl FromStr for Requirement {
+    type Err = IpCalculatorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || IpCalculatorError::ArgumentsError(format!(
+            "Invalid requirement '{}'! Expected: <name>=<hosts>",
+            input.trim()
+        ));
+        let (name, hosts) = input.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        let hosts = hosts.trim().parse::<u128>().map_err(|_| invalid())?;
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        if hosts == 0 {
+            return Err(IpCalculatorError::ArgumentsError(format!(
+                "Requirement '{}' must ask for at least one host",
+                name
+            )));
+        }
+        Ok(Requirement { name: name.to_string(), hosts })
+    }
+}
+
+pub fn parse_requirements(input: &str) -> Result<Vec<Requirement>, IpCalculatorError> {
+    let requirements = list_items(input)
+        .map(|item| item.parse())
+        .collect::<Result<Vec<Requirement>, _>>()?;
+
+    for (i, requirement) in requirements.iter().enumerate() {
+        if requirements[..i].iter().any(|other| other.name == requirement.name) {
+            return Err(IpCalculatorError::ArgumentsError(format!(
+                "Duplicate requirement name '{}'",
+                requirement.name
+            )));
+        }
+    }
+    Ok(requirements)
+}
+
+pub fn plan_vlsm<A: IpFamily>(
+    parent: &Subnet<A>,
+    requirements: &[Requirement],
+) -> Result<Vec<Allocation<A>>, IpCalculatorError> {
+    let mut sized = requirements
+        .iter()
+        .map(|requirement| Ok((requirement.prefix_for::<A>()?, requirement)))
+        .collect::<Result<Vec<_>, IpCalculatorError>>()?;
+    sized.sort_by_key(|(prefix, _)| *prefix);
+
+    let end = parent.broadcast.to_bits();
+    let mut cursor = Some(parent.network.to_bits());
+    let mut allocations = Vec::with_capacity(sized.len());
+
+    for (prefix, requirement) in sized {
+        let fits = prefix >= parent.prefix
+            && cursor.is_some_and(|start| start.checked_add(A::host_bits(prefix)).is_some_and(|last| last <= end));
+        let Some(start) = cursor.filter(|_| fits) else {
+            return Err(does_not_fit(parent, requirements, requirement, prefix));
+        };
+
+        let subnet = Subnet::from_bits(start, prefix);
+        allocations.push(Allocation {
+            name: requirement.name.clone(),
+            hosts: requirement.hosts,
+            subnet,
+            wasted: Into::<u128>::into(subnet.num_hosts) - requirement.hosts,
+        });
+        cursor = (start + A::host_bits(prefix)).checked_add(1);
+    }
+
+    Ok(allocations)
+}
+
+fn does_not_fit<A: IpFamily>(
+    parent: &Subnet<A>,
+    requirements: &[Requirement],
+    failed: &Requirement,
+    prefix: u8,
+) -> IpCalculatorError {
+    let needed = requirements
+        .iter()
+        .filter_map(|requirement| requirement.prefix_for::<A>().ok())
+        .fold(0u128, |total, prefix| total.saturating_add(A::host_bits(prefix).saturating_add(1)));
+
+    IpCalculatorError::SubnetError(format!(
+        "The plan does not fit in {}: {} needs a /{} and the requirements take {} addresses, \
+         but only {} are available",
+        parent, failed.name, prefix, needed, parent.address_total()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    fn plan(parent: &str, requirements: &str) -> Result<Vec<Allocation>, IpCalculatorError> {
+        plan_vlsm(&parent.parse().unwrap(), &parse_requirements(requirements).unwrap())
+    }
+
+    #[test]
+    fn test_plan_largest_first_and_aligned() {
+        let allocations = plan("192.168.10.0/24", "wan1=2, printers=20, users=100, wan2=2").unwrap();
+        let rows: Vec<(&str, String, u128)> = allocations
+            .iter()
+            .map(|a| (a.name.as_str(), a.subnet.to_string(), a.wasted))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("users", "192.168.10.0/25".to_string(), 26),
+                ("printers", "192.168.10.128/27".to_string(), 10),
+                ("wan1", "192.168.10.160/30".to_string(), 0),
+                ("wan2", "192.168.10.164/30".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_exact_fit_and_single_hosts() {
+        let allocations = plan("10.0.0.0/28", "a=1, b=2, c=1, d=2").unwrap();
+        assert_eq!(allocations.len(), 4);
+        assert_eq!(allocations[3].subnet.to_string(), "10.0.0.12/30");
+        assert_eq!(allocations[0].wasted, 1);
+
+        let allocations = plan("10.0.0.0/24", "link=2").unwrap();
+        assert_eq!(allocations[0].subnet.prefix, 30);
+        assert_eq!(Requirement { name: "p2p".into(), hosts: 2 }.prefix_for::<Ipv4Addr>().unwrap(), 30);
+    }
+
+    #[test]
+    fn test_plan_does_not_fit() {
+        let err = plan("192.168.10.0/25", "users=100, printers=20").unwrap_err();
+        assert!(matches!(err, IpCalculatorError::SubnetError(_)));
+        assert!(err.to_string().contains("does not fit"));
+
+        assert!(plan("10.0.0.0/24", "big=300").is_err());
+        assert!(plan("10.0.0.0/24", "a=126, b=126, c=1").is_err());
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let requirements = parse_requirements("users=100\n# links\nwan1 = 2").unwrap();
+        assert_eq!(requirements[1], Requirement { name: "wan1".into(), hosts: 2 });
+        assert_eq!(requirements[0].to_string(), "users=100");
+
+        assert!(parse_requirements("users").is_err());
+        assert!(parse_requirements("users=lots").is_err());
+        assert!(parse_requirements("users=0").is_err());
+        assert!(parse_requirements("=4").is_err());
+        assert!(parse_requirements("a=4, a=8").is_err());
+    }
+
+    #[test]
+    fn test_plan_ipv6() {
+        let parent: Subnet<Ipv6Addr> = "2001:db8::/48".parse().unwrap();
+        let requirements = parse_requirements("lan=1000, dmz=20").unwrap();
+        let allocations = plan_vlsm(&parent, &requirements).unwrap();
+        assert_eq!(allocations[0].subnet.to_string(), "2001:db8::/118");
+        assert_eq!(allocations[1].subnet.to_string(), "2001:db8::400/123");
+        assert_eq!(allocations[1].wasted, 12);
+    }
+}
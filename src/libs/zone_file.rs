@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use crate::libs::calc_ip::{is_ipv6, AddressCount, IpCalculatorError, IpFamily, Subnet};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::reverse_dns::{classless_zone, ptr_owner, reverse_zones};
+
+const DEFAULT_NAMESERVER: &str = "ns1.example.net.";
+const MAX_RECORDS: u128 = 65536;
+
+#[derive(Debug, Clone)]
+pub struct HostnameTemplate<A: IpFamily = Ipv4Addr> {
+    template: String,
+    names: Option<HashMap<A, String>>,
+}
+
+impl<A: IpFamily> HostnameTemplate<A> {
+    pub fn new(template: &str) -> Self {
+        HostnameTemplate { template: template.to_string(), names: None }
+    }
+
+    pub fn with_names(template: &str, csv: &str) -> Result<Self, IpCalculatorError> {
+        let mut names = HashMap::new();
+
+        for (number, line) in csv.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || IpCalculatorError::InputError(format!(
+                "Invalid name mapping on line {}: expected <IP>,<name> but got '{}'",
+                number + 1,
+                line
+            ));
+
+            let (ip, name) = line.split_once(',').ok_or_else(invalid)?;
+            let (ip, name) = (ip.trim(), name.trim());
+            if is_ipv6(ip) != (A::BITS == 128) {
+                continue;
+            }
+            match ip.parse::<A>() {
+                Ok(ip) if !name.is_empty() => {
+                    names.insert(ip, name.to_string());
+                },
+                Err(_) if number == 0 => continue,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(HostnameTemplate { template: template.to_string(), names: Some(names) })
+    }
+
+    pub fn render(&self, ip: A) -> Option<String> {
+        let mut hostname = self.template.clone();
+        if hostname.contains("{name}") {
+            hostname = hostname.replace("{name}", self.names.as_ref()?.get(&ip)?);
+        }
+        hostname = hostname.replace("{ip}", &ip.to_string().replace(['.', ':'], "-"));
+
+        let bits = ip.to_bits();
+        if A::BITS == 32 {
+            for octet in 1..=4u8 {
+                let value = (bits >> (32 - 8 * octet as u32)) & 0xFF;
+                hostname = hostname.replace(&format!("{{octet{}}}", octet), &value.to_string());
+            }
+        } else {
+            for hextet in 1..=8u8 {
+                let value = (bits >> (128 - 16 * hextet as u32)) & 0xFFFF;
+                hostname = hostname.replace(&format!("{{hextet{}}}", hextet), &format!("{:x}", value));
+            }
+        }
+
+        Some(fully_qualified(&hostname))
+    }
+
+    pub fn listed(&self) -> Option<Vec<A>> {
+        let mut ips: Vec<A> = self.names.as_ref()?.keys().copied().collect();
+        ips.sort_unstable_by_key(|ip| ip.to_bits());
+        Some(ips)
+    }
+}
+
+fn fully_qualified(name: &str) -> String {
+    if name.ends_with('.') { name.to_string() } else { format!("{}.", name) }
+}
+
+#[derive(Debug, Clone)]
+pub struct PtrRecord<A: IpFamily = Ipv4Addr> {
+    pub ip: A,
+    pub owner: String,
+    pub hostname: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ZoneFile<A: IpFamily = Ipv4Addr> {
+    pub origin: String,
+    pub nameservers: Vec<String>,
+    pub records: Vec<PtrRecord<A>>,
+    pub unnamed: AddressCount,
+}
+
+impl<A: IpFamily> ZoneFile<A> {
+    pub fn build(
+        subnet: &Subnet<A>,
+        template: &HostnameTemplate<A>,
+        nameservers: &[String],
+    ) -> Result<Self, IpCalculatorError> {
+        let origin = match classless_zone(subnet) {
+            Some(zone) => zone,
+            None => {
+                let zones = reverse_zones(subnet);
+                if zones.len() > 1 {
+                    return Err(IpCalculatorError::SubnetError(format!(
+                        "{} spans {} reverse zones ({} ... {}); generate one zone file per zone",
+                        subnet,
+                        zones.len(),
+                        zones[0],
+                        zones[zones.len() - 1]
+                    )));
+                }
+                zones[0].clone()
+            },
+        };
+
+        let first = subnet.first_usable.unwrap_or(subnet.network).to_bits();
+        let last = subnet.last_usable.unwrap_or(subnet.broadcast).to_bits();
+        let host_count = AddressCount::from_host_bits(last - first);
+        let hosts: Vec<A> = match template.listed() {
+            Some(listed) => listed
+                .into_iter()
+                .filter(|ip| (first..=last).contains(&ip.to_bits()))
+                .collect(),
+            None if host_count > MAX_RECORDS.into() => {
+                return Err(IpCalculatorError::SubnetError(format!(
+                    "{} has {} hosts, more than the {} PTR records a generated zone may hold; \
+                     use --names to list only the hosts that need records",
+                    subnet, host_count, MAX_RECORDS
+                )));
+            },
+            None => subnet.hosts().collect(),
+        };
+
+        let suffix = format!(".{}", origin);
+        let mut records = Vec::new();
+        for ip in hosts {
+            let Some(hostname) = template.render(ip) else {
+                continue;
+            };
+            let owner = ptr_owner(subnet, ip);
+            records.push(PtrRecord {
+                ip,
+                owner: owner.strip_suffix(&suffix).unwrap_or(&owner).to_string(),
+                hostname,
+            });
+        }
+
+        let nameservers = if nameservers.is_empty() {
+            vec![DEFAULT_NAMESERVER.to_string()]
+        } else {
+            nameservers.iter().map(|ns| fully_qualified(ns)).collect()
+        };
+
+        let unnamed = host_count.saturating_sub((records.len() as u128).into());
+        Ok(ZoneFile { origin, nameservers, records, unnamed })
+    }
+}
+
+impl<A: IpFamily> ToJson for PtrRecord<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("ip", Json::string(self.ip)),
+            ("owner", self.owner.as_str().into()),
+            ("hostname", self.hostname.as_str().into()),
+        ])
+    }
+}
+
+impl<A: IpFamily> ToJson for ZoneFile<A> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("origin", self.origin.as_str().into()),
+            ("nameservers", self.nameservers.clone().into()),
+            ("records", Json::array(&self.records)),
+            ("unnamed", self.unnamed.into()),
+        ])
+    }
+}
+
+impl<A: IpFamily> fmt::Display for ZoneFile<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let primary = &self.nameservers[0];
+        let contact = match primary.split_once('.') {
+            Some((_, domain)) if !domain.is_empty() => format!("hostmaster.{}", domain),
+            _ => "hostmaster.example.net.".to_string(),
+        };
+
+        writeln!(f, "$ORIGIN {}.", self.origin)?;
+        writeln!(f, "$TTL 3600")?;
+        writeln!(f, "@\tIN\tSOA\t{} {} (", primary, contact)?;
+        writeln!(f, "\t\t\t1\t; serial (placeholder, update before loading)")?;
+        writeln!(f, "\t\t\t3600\t; refresh")?;
+        writeln!(f, "\t\t\t900\t; retry")?;
+        writeln!(f, "\t\t\t604800\t; expire")?;
+        writeln!(f, "\t\t\t3600 )\t; negative caching TTL")?;
+        for nameserver in &self.nameservers {
+            writeln!(f, "@\tIN\tNS\t{}", nameserver)?;
+        }
+        writeln!(f)?;
+        for record in &self.records {
+            writeln!(f, "{}\tIN\tPTR\t{}", record.owner, record.hostname)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_template_render() {
+        let template = HostnameTemplate::new("host-{octet3}-{octet4}.example.net");
+        assert_eq!(template.render(Ipv4Addr::new(192, 0, 2, 65)).unwrap(), "host-2-65.example.net.");
+
+        let template = HostnameTemplate::new("{ip}.{hextet8}.example.net.");
+        assert_eq!(
+            template.render("2001:db8::ab".parse::<Ipv6Addr>().unwrap()).unwrap(),
+            "2001-db8--ab.ab.example.net."
+        );
+    }
+
+    #[test]
+    fn test_names_from_csv() {
+        let csv = "ip,name\n192.0.2.1, gw.example.net\n# printers\n192.0.2.10,printer.example.net.\n2001:db8::1,v6\n";
+        let template = HostnameTemplate::<Ipv4Addr>::with_names("{name}", csv).unwrap();
+        assert_eq!(template.render(Ipv4Addr::new(192, 0, 2, 1)).unwrap(), "gw.example.net.");
+        assert_eq!(template.render(Ipv4Addr::new(192, 0, 2, 10)).unwrap(), "printer.example.net.");
+        assert!(template.render(Ipv4Addr::new(192, 0, 2, 2)).is_none());
+
+        let err = HostnameTemplate::<Ipv4Addr>::with_names("{name}", "192.0.2.1,a\nbad line").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        assert!(HostnameTemplate::<Ipv4Addr>::with_names("{name}", "192.0.2.1,").is_err());
+    }
+
+    #[test]
+    fn test_zone_file_skips_network_and_broadcast() {
+        let template = HostnameTemplate::new("host-{octet4}.example.net");
+        let zone = ZoneFile::build(&"192.0.2.0/24".parse::<Subnet>().unwrap(), &template, &[]).unwrap();
+        assert_eq!(zone.origin, "2.0.192.in-addr.arpa");
+        assert_eq!(zone.records.len(), 254);
+        assert_eq!(zone.records[0].owner, "1");
+
+        let text = zone.to_string();
+        assert!(text.starts_with("$ORIGIN 2.0.192.in-addr.arpa.\n"));
+        assert!(text.contains("@\tIN\tNS\tns1.example.net.\n"));
+        assert!(text.contains("254\tIN\tPTR\thost-254.example.net.\n"));
+        assert!(!text.contains("\n0\tIN\tPTR"));
+        assert!(!text.contains("\n255\tIN\tPTR"));
+
+        let link = ZoneFile::build(&"192.0.2.8/31".parse::<Subnet>().unwrap(), &template, &[]).unwrap();
+        assert_eq!(link.records.len(), 2);
+        let host = ZoneFile::build(&"192.0.2.9/32".parse::<Subnet>().unwrap(), &template, &[]).unwrap();
+        assert_eq!(host.records.len(), 1);
+    }
+
+    #[test]
+    fn test_classless_zone_file() {
+        let template = HostnameTemplate::new("h{octet4}.example.net");
+        let nameservers = vec!["ns.customer.example".to_string()];
+        let zone = ZoneFile::build(&"192.0.2.64/26".parse::<Subnet>().unwrap(), &template, &nameservers).unwrap();
+        assert_eq!(zone.origin, "64/26.2.0.192.in-addr.arpa");
+        assert_eq!(zone.records.len(), 62);
+        assert_eq!(zone.records[0].owner, "65");
+        assert!(zone.to_string().contains("SOA\tns.customer.example. hostmaster.customer.example. ("));
+
+        assert!(ZoneFile::build(&"192.168.4.0/22".parse::<Subnet>().unwrap(), &template, &[]).is_err());
+    }
+
+    #[test]
+    fn test_large_ipv6_zone_needs_names() {
+        let subnet: Subnet<Ipv6Addr> = "2001:db8:0:1::/64".parse().unwrap();
+        let err = ZoneFile::build(&subnet, &HostnameTemplate::new("h-{hextet8}.example.net"), &[]).unwrap_err();
+        assert!(matches!(err, IpCalculatorError::SubnetError(_)));
+        assert!(err.to_string().contains("--names"));
+
+        let csv = "2001:db8:0:1::10,db.example.net\n2001:db8:0:1::1,gw.example.net\n2001:db8:0:2::1,elsewhere.example.net\n";
+        let template = HostnameTemplate::with_names("{name}", csv).unwrap();
+        let zone = ZoneFile::build(&subnet, &template, &[]).unwrap();
+        assert_eq!(zone.origin, "1.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa");
+        assert_eq!(zone.records.iter().map(|r| r.hostname.as_str()).collect::<Vec<_>>(), ["gw.example.net.", "db.example.net."]);
+        assert_eq!(zone.unnamed, AddressCount::Exact((1u128 << 64) - 2));
+    }
+}
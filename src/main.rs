@@ -1,15 +1,25 @@
-use crate::utils::handle_arguments::handle_arguments;
-
-mod utils;
-mod libs;
+use ip_calculator::libs::json::ToJson;
+use ip_calculator::libs::render::{render_error, Style};
+use ip_calculator::utils::handle_arguments::{handle_arguments, wants_json, wants_quiet};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    let json = wants_json(&args);
+    let quiet = wants_quiet(&args);
     match handle_arguments(args) {
-        Ok(message) => println!("{}", message),
+        Ok(outcome) => {
+            if !quiet && !outcome.text.is_empty() {
+                println!("{}", outcome.text);
+            }
+            std::process::exit(outcome.exit_code);
+        }
         Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
+            if json && !quiet {
+                println!("{}", e.to_json());
+            } else if !json {
+                eprintln!("{}", render_error(&e, Style::Colored));
+            }
+            std::process::exit(e.exit_code());
         }
     }
 }
@@ -0,0 +1,260 @@
+use crate::libs::calc_ip::IpCalculatorError;
+use crate::libs::json::{Json, ToJson};
+use crate::libs::render::{self, render, Format, Style};
+use crate::utils::handle_arguments::{run_line, LineOptions, Outcome};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+const JSON_HEAD: &str = "{\"command\":\"batch\",\"results\":[";
+
+pub struct BatchLine {
+    pub line: usize,
+    pub input: String,
+    pub result: Result<Json, IpCalculatorError>,
+    pub text: String,
+}
+
+pub struct BatchReport {
+    pub lines: Vec<BatchLine>,
+}
+
+impl BatchReport {
+    pub fn failures(&self) -> impl Iterator<Item = &BatchLine> {
+        self.lines.iter().filter(|line| line.result.is_err())
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.failures().next().is_some() { 1 } else { 0 }
+    }
+
+    fn summary(&self) -> Json {
+        let failed: Vec<Json> = self.failures().map(|line| line.line.into()).collect();
+        Json::object([
+            ("lines", self.lines.len().into()),
+            ("succeeded", (self.lines.len() - failed.len()).into()),
+            ("failed", failed.len().into()),
+            ("failed_lines", Json::Array(failed)),
+        ])
+    }
+}
+
+pub fn run_batch(path: Option<&str>, options: &LineOptions, quiet: bool) -> Result<Outcome, IpCalculatorError> {
+    let file = match path {
+        Some(path) => Some(File::open(path).map_err(|e| IpCalculatorError::InputError(
+            format!("Failed to read batch file '{}': {}", path, e)
+        ))?),
+        None => None,
+    };
+
+    if options.json && !quiet {
+        print!("{}", JSON_HEAD);
+    }
+    let mut first = true;
+    let emit = |line: &BatchLine| {
+        if quiet {
+            return;
+        }
+        if options.json {
+            print!("{}{}", if first { "" } else { "," }, line.to_json());
+        } else {
+            println!("{}", line_text(line, Style::Colored));
+        }
+        first = false;
+        io::stdout().flush().ok();
+    };
+    let report = match file {
+        Some(file) => process_lines(BufReader::new(file), options, emit),
+        None => process_lines(io::stdin().lock(), options, emit),
+    };
+    let report = match report {
+        Ok(report) => report,
+        Err(e) if options.json && !quiet => {
+            return Ok(Outcome { text: json_error_tail(&e), exit_code: e.exit_code() });
+        },
+        Err(e) => return Err(e),
+    };
+
+    let text = if options.json {
+        json_tail(&report)
+    } else {
+        summary_text(&report)
+    };
+    Ok(Outcome { text, exit_code: report.exit_code() })
+}
+
+pub fn process_lines<R: BufRead>(
+    mut reader: R,
+    options: &LineOptions,
+    mut emit: impl FnMut(&BatchLine),
+) -> Result<BatchReport, IpCalculatorError> {
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+    let mut number = 0;
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer).map_err(|e| IpCalculatorError::InputError(
+            format!("Failed to read batch input: {}", e)
+        ))?;
+        if read == 0 {
+            break;
+        }
+        number += 1;
+        let line = String::from_utf8_lossy(&buffer);
+        let input = line.trim();
+        if input.is_empty() || input.starts_with('#') {
+            continue;
+        }
+        let line = process_line(number, input, options);
+        emit(&line);
+        lines.push(line);
+    }
+    Ok(BatchReport { lines })
+}
+
+fn process_line(line: usize, input: &str, options: &LineOptions) -> BatchLine {
+    let mut tokens: Vec<String> = input.split_whitespace().map(str::to_string).collect();
+    if tokens[0].ends_with("ip_calculator") {
+        tokens.remove(0);
+    }
+
+    let (result, text) = match run_line(tokens, options, "--batch") {
+        Ok(output) => {
+            for warning in render::warnings(&output) {
+                eprintln!("Line {}: {}", line, warning);
+            }
+            (Ok(output.to_json()), render(&output, Format::Colored))
+        },
+        Err(e) => {
+            let text = render::render_error(&e, Style::Colored);
+            (Err(e), text)
+        },
+    };
+    BatchLine { line, input: input.to_string(), result, text }
+}
+
+fn line_text(line: &BatchLine, style: Style) -> String {
+    format!(
+        "{} {}\n{}\n{}",
+        style.label(format!("Line {}:", line.line)),
+        line.input,
+        line.text,
+        render::SEPARATOR
+    )
+}
+
+fn summary_text(report: &BatchReport) -> String {
+    let mut lines = Vec::new();
+    let failed: Vec<&BatchLine> = report.failures().collect();
+    lines.push(format!(
+        "\nProcessed {} lines: {} succeeded, {} failed",
+        report.lines.len(),
+        report.lines.len() - failed.len(),
+        failed.len()
+    ));
+    for line in failed {
+        if let Err(e) = &line.result {
+            lines.push(format!("  Line {}: {}", line.line, e));
+        }
+    }
+    lines.join("\n")
+}
+
+fn json_tail(report: &BatchReport) -> String {
+    format!("],\"summary\":{}}}", report.summary())
+}
+
+fn json_error_tail(error: &IpCalculatorError) -> String {
+    let error = error.to_json().get("error").cloned().unwrap_or(Json::Null);
+    format!("],\"error\":{}}}", error)
+}
+
+impl ToJson for BatchLine {
+    fn to_json(&self) -> Json {
+        let mut entry = Json::object([
+            ("line", self.line.into()),
+            ("input", self.input.as_str().into()),
+        ]);
+        match &self.result {
+            Ok(result) => entry.push("result", result.clone()),
+            Err(e) => {
+                if let Some(error) = e.to_json().get("error") {
+                    entry.push("error", error.clone());
+                }
+            },
+        }
+        entry
+    }
+}
+
+impl ToJson for BatchReport {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("command", "batch".into()),
+            ("results", Json::array(&self.lines)),
+            ("summary", self.summary()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> LineOptions {
+        LineOptions { wildcard: false, verbose: false, json: false, columns: None }
+    }
+
+    #[test]
+    fn test_process_lines_mixed_input() {
+        let input = "10.0.0.0/24\n\n# comment\n192.168.1.10 255.255.255.0\n-cip 300.1.1.1\n./ip_calculator -cip 10.1.1.1\n";
+        let report = process_lines(input.as_bytes(), &options(), |_| {}).unwrap();
+
+        assert_eq!(report.lines.iter().map(|line| line.line).collect::<Vec<_>>(), vec![1, 4, 5, 6]);
+        assert_eq!(report.failures().map(|line| line.line).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(report.exit_code(), 1);
+        assert!(report.lines[1].text.contains("192.168.1.0"));
+    }
+
+    #[test]
+    fn test_batch_json_summary() {
+        let report = process_lines("-cip 10.0.0.1\n-h\n".as_bytes(), &options(), |_| {}).unwrap();
+        let json = report.to_json();
+        let summary = json.get("summary").unwrap();
+
+        assert_eq!(summary.get("failed").unwrap().to_string(), "1");
+        assert_eq!(summary.get("failed_lines").unwrap().to_string(), "[2]");
+        assert!(json.to_string().contains("\"command\":\"check-ip\""));
+    }
+
+    #[test]
+    fn test_invalid_utf8_line_is_streamed_and_does_not_stop_the_batch() {
+        let input: &[u8] = b"-cip 10.0.0.1\n-cip 10.0.0.\xff\n-cip 10.0.0.3\n";
+        let mut streamed = Vec::new();
+        let report = process_lines(input, &options(), |line| streamed.push(line.to_json().to_string())).unwrap();
+
+        assert_eq!(report.lines.len(), 3);
+        assert_eq!(report.failures().map(|line| line.line).collect::<Vec<_>>(), vec![2]);
+        assert!(report.lines[1].input.contains('\u{FFFD}'));
+
+        let document = format!("{}{}{}", JSON_HEAD, streamed.join(","), json_tail(&report));
+        assert_eq!(document, report.to_json().to_string());
+    }
+
+    struct FailingReader;
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("device lost"))
+        }
+    }
+
+    #[test]
+    fn test_read_error_closes_the_json_document() {
+        let error = process_lines(BufReader::new(FailingReader), &options(), |_| {}).err().unwrap();
+        assert_eq!(
+            format!("{}{}", JSON_HEAD, json_error_tail(&error)),
+            "{\"command\":\"batch\",\"results\":[],\"error\":\
+             {\"type\":\"InputError\",\"message\":\"Input error: Failed to read batch input: device lost\"}}"
+        );
+    }
+}
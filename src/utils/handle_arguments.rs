@@ -1,26 +1,75 @@
 use crate::libs::calc_ip::{
-    execute_command, is_cidr_or_mask, mask_to_cidr, parse_mask_or_cidr, InputType, Command, IpCalculatorError, CommandHelp
+    execute_command, is_ipv6, mask_to_cidr, parse_cidr, parse_mask_or_cidr_with_hint, parse_mask_with_hint, InputType, Command, CommandOutput, IpCalculatorError, CommandHelp, SetOperation
 };
-use std::net::Ipv4Addr;
+use crate::libs::completions::{self, CompletionShell};
+use crate::libs::export::{parse_columns, Column, OutputFile};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::man_page::{self, ManFormat};
+use crate::libs::render::{self, render, Format, Style};
+use crate::utils::batch::run_batch;
+use crate::utils::shell::run_shell;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
-pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError> {
+pub struct LineOptions {
+    pub wildcard: bool,
+    pub verbose: bool,
+    pub json: bool,
+    pub columns: Option<Vec<Column>>,
+}
+
+pub struct Outcome {
+    pub text: String,
+    pub exit_code: i32,
+}
+
+impl Outcome {
+    fn success(text: String) -> Self {
+        Outcome { text, exit_code: 0 }
+    }
+}
+
+pub fn handle_arguments(args: Vec<String>) -> Result<Outcome, IpCalculatorError> {
+    let wildcard = args.iter().any(|arg| arg == "--wildcard");
+    let verbose = args.iter().any(|arg| arg == "-v" || arg == "--verbose");
+    let json = wants_json(&args);
+    let quiet = wants_quiet(&args);
+    let args: Vec<String> = args
+        .into_iter()
+        .filter(|arg| !matches!(arg.as_str(), "--wildcard" | "-v" | "--verbose" | "--json" | "-q" | "--quiet"))
+        .collect();
+    let (args, columns) = split_columns(&args)?;
+
     if args.len() < 2 {
         return Err(IpCalculatorError::ArgumentsError(
             "Usage: ./ip_calculator <options> <arguments>".to_string()
         ));
     }
 
-    let command = match args[1].as_str() {
+    match args[1].as_str() {
+        "-h" | "--help" if json => {
+            let help = match args.get(2) {
+                None => Json::object([
+                    ("command", "help".into()),
+                    ("commands", Json::array(CommandHelp::get_all())),
+                ]),
+                Some(name) => match CommandHelp::find_by_name_or_alias(name) {
+                    Some(cmd_help) => Json::object([("command", "help".into()), ("help", cmd_help.to_json())]),
+                    None => return Err(IpCalculatorError::ArgumentsError(
+                        format!("Unknown command '{}'. Use --help for a list of available commands.", name)
+                    )),
+                },
+            };
+            return Ok(Outcome::success(help.to_string()));
+        },
         "-h" | "--help" => {
             if args.len() == 2 {
-                CommandHelp::display_command_list();
-                return Ok("Help information displayed.".to_string());
+                return Ok(Outcome::success(render::command_list(Style::Colored)));
             } else {
                 match CommandHelp::find_by_name_or_alias(&args[2]) {
                     Some(cmd_help) => {
-                        cmd_help.display_command_help();
-                        return Ok("Command help displayed.".to_string());
+                        return Ok(Outcome::success(render::command_help(&cmd_help, Style::Colored)));
                     },
                     None => {
                         return Err(IpCalculatorError::ArgumentsError(
@@ -30,6 +79,61 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 }
             }
         },
+        "-b" | "--batch" => {
+            let input = match args.get(2).map(String::as_str) {
+                None | Some("-") => None,
+                Some(path) => Some(path),
+            };
+            let options = LineOptions { wildcard, verbose, json, columns };
+            return run_batch(input, &options, quiet);
+        },
+        "--completions" | "--generate-man" if json => {
+            return Err(IpCalculatorError::ArgumentsError(
+                format!("{} prints a script and cannot be combined with --json", args[1])
+            ));
+        },
+        "--completions" => {
+            let shell = args.get(2).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                "Missing shell name! Usage: ./ip_calculator --completions <bash|zsh|fish>".to_string()
+            ))?;
+            return Ok(Outcome::success(completions::generate(shell.parse::<CompletionShell>()?)));
+        },
+        "--generate-man" => {
+            let format = match args.get(2) {
+                Some(format) => format.parse::<ManFormat>()?,
+                None => ManFormat::Roff,
+            };
+            return Ok(Outcome::success(man_page::generate(format)));
+        },
+        "--shell" => {
+            return run_shell(LineOptions { wildcard, verbose, json, columns });
+        },
+        _ => {},
+    }
+
+    let command = parse_command(&args, &columns, wildcard, verbose)?;
+    let output = run_command(command, &columns)?;
+
+    for warning in render::warnings(&output) {
+        eprintln!("{}", warning);
+    }
+    let format = if json { Format::Json } else { Format::Colored };
+    Ok(Outcome { text: render(&output, format), exit_code: output.exit_code() })
+}
+
+fn parse_command(
+    args: &[String],
+    columns: &Option<Vec<Column>>,
+    wildcard: bool,
+    verbose: bool,
+) -> Result<Command, IpCalculatorError> {
+    if args.len() < 2 {
+        return Err(IpCalculatorError::ArgumentsError(
+            "Usage: ./ip_calculator <options> <arguments>".to_string()
+        ));
+    }
+
+    let command = match args[1].as_str() {
         "-s" | "--subnets" => {
             if args.len() < 4 {
                 return Err(IpCalculatorError::ArgumentsError(
@@ -37,7 +141,7 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 ));
             }
 
-            let cidr = args[2].clone();
+            let cidr = apply_wildcard_hint(&args[2], wildcard)?;
             let prefix = args[3].parse::<u8>().map_err(|_| 
                 IpCalculatorError::InvalidPrefix("Invalid prefix value! Prefix should be a valid number between 0 and 32.".to_string())
             )?;
@@ -48,28 +152,28 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
             let mut i = 4;
             while i < args.len() {
                 let arg = args[i].as_str();
-                if arg.starts_with("-f") {
-                    let val = if arg == "-f" {
+                if let Some(inline) = arg.strip_prefix("-f") {
+                    let val = if inline.is_empty() {
                         i += 1;
                         if i >= args.len() {
                             return Err(IpCalculatorError::ArgumentsError("Missing value for -f option".to_string()));
                         }
                         args[i].as_str()
                     } else {
-                        &arg[2..]
+                        inline
                     };
                     filter = Some(val.parse::<usize>().map_err(|_|
                         IpCalculatorError::ArgumentsError("Invalid filter value".to_string())
                     )?);
-                } else if arg.starts_with("-p") {
-                    let val = if arg == "-p" {
+                } else if let Some(inline) = arg.strip_prefix("-p") {
+                    let val = if inline.is_empty() {
                         i += 1;
                         if i >= args.len() {
                             return Err(IpCalculatorError::ArgumentsError("Missing value for -p option".to_string()));
                         }
                         args[i].as_str()
                     } else {
-                        &arg[2..]
+                        inline
                     };
                     let p = val.parse::<usize>().map_err(|_|
                         IpCalculatorError::ArgumentsError("Invalid page number".to_string())
@@ -80,11 +184,11 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                     if i >= args.len() {
                         return Err(IpCalculatorError::ArgumentsError("Missing value for -o option".to_string()));
                     }
-                    output_file = Some(args[i].clone());
-                } else if let Some(val) = arg.strip_prefix("-o") {
-                    output_file = Some(val.to_string());
+                    output_file = Some(OutputFile::new(&args[i], columns.clone())?);
                 } else if let Some(val) = arg.strip_prefix("--output=") {
-                    output_file = Some(val.to_string());
+                    output_file = Some(OutputFile::new(val, columns.clone())?);
+                } else if let Some(val) = arg.strip_prefix("-o") {
+                    output_file = Some(OutputFile::new(val, columns.clone())?);
                 }
                 i += 1;
             }
@@ -98,17 +202,18 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
             }
         },
         "--get-subnet" => {
+            let (args, output_file) = split_output_file(args, columns)?;
             if args.len() < 5 {
                 return Err(IpCalculatorError::ArgumentsError(
                     "Missing arguments for specific subnet retrieval! Expected: <CIDR> <prefix> <index>".to_string()
                 ));
             }
 
-            let cidr = args[2].clone();
+            let cidr = apply_wildcard_hint(&args[2], wildcard)?;
             let prefix = args[3].parse::<u8>().map_err(|_| 
                 IpCalculatorError::InvalidPrefix("Invalid prefix value!".to_string())
             )?;
-            let index = args[4].parse::<u32>().map_err(|_| 
+            let index = args[4].parse::<u128>().map_err(|_| 
                 IpCalculatorError::SubnetError("Invalid subnet index!".to_string())
             )?;
 
@@ -116,6 +221,7 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 cidr,
                 prefix,
                 index,
+                output_file,
             }
         },
         "-same" | "--same-subnet" => {
@@ -127,15 +233,22 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 ));
             }
 
-            let ip1 = Ipv4Addr::from_str(&args[2]).map_err(|_| 
+            let ip1 = IpAddr::from_str(&args[2]).map_err(|_| 
                 IpCalculatorError::InvalidIP("Invalid IP address format for first IP".to_string())
             )?;
-            let ip2 = Ipv4Addr::from_str(&args[3]).map_err(|_| 
+            let ip2 = IpAddr::from_str(&args[3]).map_err(|_| 
                 IpCalculatorError::InvalidIP("Invalid IP address format for second IP".to_string())
             )?;
-            let mask1 = parse_mask_or_cidr(&args[4], InputType::Mask)?.expect_mask();
+            let parse_same_subnet_mask = |input: &str| -> Result<IpAddr, IpCalculatorError> {
+                if ip1.is_ipv6() {
+                    Ok(IpAddr::V6(parse_mask_with_hint::<Ipv6Addr>(input, wildcard)?))
+                } else {
+                    Ok(IpAddr::V4(parse_mask_or_cidr_with_hint(input, InputType::Mask, wildcard)?.expect_mask()))
+                }
+            };
+            let mask1 = parse_same_subnet_mask(&args[4])?;
             let mask2 = if args.len() == max_args {
-                Some(parse_mask_or_cidr(&args[5], InputType::Mask)?.expect_mask())
+                Some(parse_same_subnet_mask(&args[5])?)
             } else {
                 None
             };
@@ -160,6 +273,7 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
             }
             Command::CheckMask {
                 mask: args[2].clone(),
+                wildcard,
             }
         },
         "-fr" | "--find-range" => {
@@ -169,9 +283,14 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 ));
             }
 
-            let calculate_subnet = crate::libs::calc_ip::calculate_subnet;
             let (cidr, range_size, exclusions) = if args[2].contains('/') {
-                calculate_subnet(&args[2]).map_err(|_| IpCalculatorError::InvalidCIDR(
+                let cidr = apply_wildcard_hint(&args[2], wildcard)?;
+                let valid = if is_ipv6(&cidr) {
+                    parse_cidr::<Ipv6Addr>(&cidr).map(|_| ())
+                } else {
+                    parse_cidr::<Ipv4Addr>(&cidr).map(|_| ())
+                };
+                valid.map_err(|_| IpCalculatorError::InvalidCIDR(
                     format!("Invalid CIDR format for {}", args[2])
                 ))?;
 
@@ -179,12 +298,12 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                     IpCalculatorError::InvalidRange("Invalid number of hosts specified!".to_string())
                 )?;
 
-                let exclusions: Vec<Ipv4Addr> = args[4..]
+                let exclusions: Vec<IpAddr> = args[4..]
                     .iter()
-                    .filter_map(|ip| Ipv4Addr::from_str(ip).ok())
+                    .filter_map(|ip| IpAddr::from_str(ip).ok())
                     .collect();
 
-                (args[2].clone(), range_size, exclusions)
+                (cidr, range_size, exclusions)
             } else {
                 if args.len() < 5 {
                     return Err(IpCalculatorError::ArgumentsError(
@@ -192,16 +311,20 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                     ));
                 }
 
-                let prefix = parse_mask_or_cidr(&args[3], InputType::Cidr)?.expect_cidr();
+                let prefix = if is_ipv6(&args[2]) {
+                    mask_to_cidr(parse_mask_with_hint::<Ipv6Addr>(&args[3], wildcard)?)?
+                } else {
+                    parse_mask_or_cidr_with_hint(&args[3], InputType::Cidr, wildcard)?.expect_cidr()
+                };
                 let cidr = format!("{}/{}", args[2], prefix);
 
                 let range_size = args[4].parse::<usize>().map_err(|_| 
                     IpCalculatorError::InvalidRange("Invalid number of hosts specified!".to_string())
                 )?;
 
-                let exclusions: Vec<Ipv4Addr> = args[5..]
+                let exclusions: Vec<IpAddr> = args[5..]
                     .iter()
-                    .filter_map(|ip| Ipv4Addr::from_str(ip).ok())
+                    .filter_map(|ip| IpAddr::from_str(ip).ok())
                     .collect();
 
                 (cidr, range_size, exclusions)
@@ -213,24 +336,249 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
                 exclusions,
             }
         },
+        "--set" => {
+            if args.len() < 4 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing arguments for set operation! Expected: <operation> <list|file> [list|file]".to_string()
+                ));
+            }
+            let operation = SetOperation::from_str(&args[2])?;
+            let left = read_list_argument(&args[3])?;
+            let right = match args.get(4) {
+                Some(arg) => Some(read_list_argument(arg)?),
+                None => None,
+            };
+            Command::Set { operation, left, right }
+        },
+        "-agg" | "--aggregate" => {
+            let mut lists = Vec::new();
+            let mut max_entries = None;
+            let mut i = 2;
+            while i < args.len() {
+                if args[i] == "--max" || args[i] == "-m" {
+                    i += 1;
+                    let value = args.get(i).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                        "Missing value for --max option".to_string()
+                    ))?;
+                    max_entries = Some(value.parse::<usize>().map_err(|_|
+                        IpCalculatorError::ArgumentsError("Invalid --max value".to_string())
+                    )?);
+                } else {
+                    lists.push(read_list_argument(&args[i])?);
+                }
+                i += 1;
+            }
+            if lists.is_empty() {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing prefixes to aggregate! Expected: <prefix|list|file>... [--max <entries>]".to_string()
+                ));
+            }
+            Command::Aggregate {
+                prefixes: lists.join("\n"),
+                max_entries,
+            }
+        },
+        "-super" | "--supernet" => {
+            if args.len() < 3 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing addresses for supernet calculation! Expected: <IP|CIDR|list|file>...".to_string()
+                ));
+            }
+            let lists = args[2..]
+                .iter()
+                .map(|arg| read_list_argument(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            Command::Supernet {
+                prefixes: lists.join("\n"),
+            }
+        },
+        "-ex" | "--exclude" => {
+            if args.len() < 4 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing arguments for exclusion! Expected: <CIDR> <prefix|range|list|file>... [-o <file>]".to_string()
+                ));
+            }
+
+            let mut lists = Vec::new();
+            let mut output_file = None;
+            let mut i = 3;
+            while i < args.len() {
+                let arg = args[i].as_str();
+                if arg == "-o" || arg == "--output" {
+                    i += 1;
+                    let value = args.get(i).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                        "Missing value for -o option".to_string()
+                    ))?;
+                    output_file = Some(OutputFile::new(value, columns.clone())?);
+                } else if let Some(val) = arg.strip_prefix("--output=") {
+                    output_file = Some(OutputFile::new(val, columns.clone())?);
+                } else {
+                    lists.push(read_list_argument(arg)?);
+                }
+                i += 1;
+            }
+
+            Command::Exclude {
+                cidr: args[2].clone(),
+                excluded: lists.join("\n"),
+                output_file,
+            }
+        },
+        "-vlsm" | "--vlsm" => {
+            if args.len() < 4 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing arguments for VLSM planning! Expected: <CIDR> <name=hosts|list|file>...".to_string()
+                ));
+            }
+            let lists = args[3..]
+                .iter()
+                .map(|arg| read_list_argument(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            Command::Vlsm {
+                cidr: args[2].clone(),
+                requirements: lists.join("\n"),
+            }
+        },
+        "-lpm" | "--lookup" => {
+            if args.len() < 4 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing arguments for route lookup! Expected: <route_file> <IP>...".to_string()
+                ));
+            }
+            let routes = std::fs::read_to_string(&args[2]).map_err(|e| IpCalculatorError::InputError(
+                format!("Failed to read route table '{}': {}", args[2], e)
+            ))?;
+            let ips = args[3..]
+                .iter()
+                .map(|ip| IpAddr::from_str(ip).map_err(|_|
+                    IpCalculatorError::InvalidIP(format!("Invalid destination IP: {}", ip))
+                ))
+                .collect::<Result<Vec<_>, _>>()?;
+            Command::Lookup { routes, ips }
+        },
+        "-cls" | "--classify" => {
+            if args.len() < 3 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing addresses to classify! Expected: <IP|CIDR>...".to_string()
+                ));
+            }
+            Command::Classify {
+                inputs: args[2..].to_vec(),
+            }
+        },
+        "-cv" | "--convert" => {
+            if args.len() < 3 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing value to convert! Expected: <dotted|integer|hex|binary>...".to_string()
+                ));
+            }
+            Command::Convert {
+                values: args[2..].to_vec(),
+            }
+        },
+        "-math" | "--ip-math" => {
+            if !(4..=5).contains(&args.len()) {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Invalid arithmetic arguments! Expected: add|sub|nth|distance|next|prev <operand> [operand]".to_string()
+                ));
+            }
+            Command::Arithmetic {
+                operation: args[2].parse()?,
+                operands: args[3..].to_vec(),
+            }
+        },
+        "-rdns" | "--reverse-dns" => {
+            if args.len() < 3 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing CIDR for reverse DNS! Expected: <CIDR> [-f <number>]".to_string()
+                ));
+            }
+            let filter = match args.get(3).map(String::as_str) {
+                None => None,
+                Some("-f") if args.len() == 5 => Some(args[4].parse::<usize>().map_err(|_|
+                    IpCalculatorError::ArgumentsError("Invalid filter value".to_string())
+                )?),
+                Some(arg) => return Err(IpCalculatorError::ArgumentsError(
+                    format!("Unexpected argument for reverse DNS: {}", arg)
+                )),
+            };
+            Command::ReverseDns {
+                cidr: apply_wildcard_hint(&args[2], wildcard)?,
+                filter,
+            }
+        },
+        "-ptr" | "--ptr-zone" => {
+            if args.len() < 4 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing arguments for PTR zone! Expected: <CIDR> <template> [--names <csv>] [--ns <nameserver>]... [-o <file>]".to_string()
+                ));
+            }
+
+            let mut names = None;
+            let mut nameservers = Vec::new();
+            let mut output_file = None;
+            let mut i = 4;
+            while i < args.len() {
+                let option = args[i].as_str();
+                let value = args.get(i + 1).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                    format!("Missing value for {} option", option)
+                ))?;
+                match option {
+                    "--names" => names = Some(std::fs::read_to_string(value).map_err(|e|
+                        IpCalculatorError::InputError(format!("Failed to read names file '{}': {}", value, e))
+                    )?),
+                    "--ns" => nameservers.push(value.clone()),
+                    "-o" | "--output" => output_file = Some(OutputFile::new(value, None)?),
+                    _ => return Err(IpCalculatorError::ArgumentsError(
+                        format!("Unknown option for PTR zone: {}", option)
+                    )),
+                }
+                i += 2;
+            }
+
+            Command::PtrZone {
+                cidr: apply_wildcard_hint(&args[2], wildcard)?,
+                template: args[3].clone(),
+                names,
+                nameservers,
+                output_file,
+            }
+        },
+        "-rc" | "--range-to-cidr" => {
+            let (args, output_file) = split_output_file(args, columns)?;
+            if args.len() < 3 {
+                return Err(IpCalculatorError::ArgumentsError(
+                    "Missing range for conversion! Expected: <start> - <end> [-o <file>]".to_string()
+                ));
+            }
+            Command::RangeToCidr {
+                range: args[2..].join(" "),
+                output_file,
+            }
+        },
         _ => {
+            let (args, output_file) = split_output_file(args, columns)?;
             if args[1].contains('/') {
                 Command::Display {
-                    cidr: args[1].clone(),
+                    cidr: apply_wildcard_hint(&args[1], wildcard)?,
+                    verbose,
+                    output_file,
                 }
             } else {
-                if args.len() == 3 {
-                    let cidr = match is_cidr_or_mask(&args[2])? {
-                        InputType::Mask => {
-                            let mask = Ipv4Addr::from_str(&args[2]).map_err(|_| 
-                                IpCalculatorError::InvalidMask("Invalid mask format!".to_string())
-                            )?;
-                            let cidr_prefix = mask_to_cidr(mask)?;
-                            format!("{}/{}", args[1], cidr_prefix)
-                        }
-                        InputType::Cidr => format!("{}/{}", args[1], args[2]),
-                    };
-                    Command::Display { cidr }
+                if args.len() == 3 && is_ipv6(&args[1]) {
+                    let prefix = mask_to_cidr(parse_mask_with_hint::<Ipv6Addr>(&args[2], wildcard)?)?;
+                    Command::Display {
+                        cidr: format!("{}/{}", args[1], prefix),
+                        verbose,
+                        output_file,
+                    }
+                } else if args.len() == 3 {
+                    let cidr_prefix = parse_mask_or_cidr_with_hint(&args[2], InputType::Cidr, wildcard)?.expect_cidr();
+                    Command::Display {
+                        cidr: format!("{}/{}", args[1], cidr_prefix),
+                        verbose,
+                        output_file,
+                    }
                 } else {
                     return Err(IpCalculatorError::ArgumentsError(
                         "Unknown command! Check the usage instructions for proper syntax.".to_string()
@@ -239,7 +587,163 @@ pub fn handle_arguments(args: Vec<String>) -> Result<String, IpCalculatorError>
             }
         }
     };
+    Ok(command)
+}
+
+pub(crate) fn run_line(
+    tokens: Vec<String>,
+    options: &LineOptions,
+    context: &str,
+) -> Result<CommandOutput, IpCalculatorError> {
+    let wildcard = options.wildcard || tokens.iter().any(|arg| arg == "--wildcard");
+    let verbose = options.verbose || tokens.iter().any(|arg| arg == "-v" || arg == "--verbose");
+    let args: Vec<String> = std::iter::once("ip_calculator".to_string())
+        .chain(tokens.into_iter().filter(|arg| {
+            !matches!(arg.as_str(), "--wildcard" | "-v" | "--verbose" | "--json" | "-q" | "--quiet")
+        }))
+        .collect();
+    let (args, columns) = split_columns(&args)?;
+
+    if let Some(arg @ ("-h" | "--help" | "-b" | "--batch" | "--shell")) = args.get(1).map(String::as_str) {
+        return Err(IpCalculatorError::ArgumentsError(
+            format!("'{}' is not available inside {}", arg, context)
+        ));
+    }
+    let columns = columns.or_else(|| options.columns.clone());
+    let command = parse_command(&args, &columns, wildcard, verbose)?;
+    run_command(command, &columns)
+}
 
-    execute_command(command)?;
-    Ok("Operation completed successfully.".to_string())
-}
\ No newline at end of file
+fn run_command(
+    command: Command,
+    columns: &Option<Vec<Column>>,
+) -> Result<CommandOutput, IpCalculatorError> {
+    if columns.is_some() && (command.output_file().is_none() || matches!(command, Command::PtrZone { .. })) {
+        return Err(IpCalculatorError::ArgumentsError(
+            "--columns only applies together with -o <file.csv|file.tsv>".to_string()
+        ));
+    }
+    let output_file = command.output_file().cloned();
+    let output = execute_command(command)?;
+    if let Some(file) = output_file {
+        let mut writer = file.create()?;
+        render::export(&mut writer, &output, file.format(), &file.columns)
+            .and_then(|_| writer.flush())
+            .map_err(|e| file.write_error(e))?;
+    }
+    Ok(output)
+}
+
+pub fn wants_json(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--json")
+}
+
+pub fn wants_quiet(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "-q" || arg == "--quiet")
+}
+
+fn split_columns(args: &[String]) -> Result<(Vec<String>, Option<Vec<Column>>), IpCalculatorError> {
+    let mut rest = Vec::new();
+    let mut columns = None;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--columns" {
+            i += 1;
+            let value = args.get(i).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                "Missing value for --columns option".to_string()
+            ))?;
+            columns = Some(parse_columns(value)?);
+        } else if let Some(value) = args[i].strip_prefix("--columns=") {
+            columns = Some(parse_columns(value)?);
+        } else {
+            rest.push(args[i].clone());
+        }
+        i += 1;
+    }
+    Ok((rest, columns))
+}
+
+fn split_output_file(
+    args: &[String],
+    columns: &Option<Vec<Column>>,
+) -> Result<(Vec<String>, Option<OutputFile>), IpCalculatorError> {
+    let mut rest = Vec::new();
+    let mut path = None;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "-o" || args[i] == "--output" {
+            i += 1;
+            let value = args.get(i).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                "Missing value for -o option".to_string()
+            ))?;
+            path = Some(value.clone());
+        } else if let Some(value) = args[i].strip_prefix("--output=") {
+            path = Some(value.to_string());
+        } else {
+            rest.push(args[i].clone());
+        }
+        i += 1;
+    }
+    let output_file = path.map(|path| OutputFile::new(&path, columns.clone())).transpose()?;
+    Ok((rest, output_file))
+}
+
+fn apply_wildcard_hint(cidr: &str, wildcard: bool) -> Result<String, IpCalculatorError> {
+    match cidr.split_once('/') {
+        Some((ip, mask)) if wildcard && mask.contains(['.', ':']) => {
+            let prefix = if is_ipv6(ip) {
+                mask_to_cidr(parse_mask_with_hint::<Ipv6Addr>(mask, true)?)?
+            } else {
+                parse_mask_or_cidr_with_hint(mask, InputType::Cidr, true)?.expect_cidr()
+            };
+            Ok(format!("{}/{}", ip, prefix))
+        },
+        _ => Ok(cidr.to_string()),
+    }
+}
+
+fn read_list_argument(arg: &str) -> Result<String, IpCalculatorError> {
+    if std::path::Path::new(arg).is_file() {
+        std::fs::read_to_string(arg).map_err(|e| IpCalculatorError::InputError(
+            format!("Failed to read list file '{}': {}", arg, e)
+        ))
+    } else {
+        Ok(arg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit_code(args: &[&str]) -> i32 {
+        let args = std::iter::once("ip_calculator").chain(args.iter().copied()).map(str::to_string).collect();
+        match handle_arguments(args) {
+            Ok(outcome) => outcome.exit_code,
+            Err(e) => e.exit_code(),
+        }
+    }
+
+    #[test]
+    fn test_input_errors_exit_with_one() {
+        let dir = std::env::temp_dir().join(format!("ip_calculator_exit_codes_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing.txt");
+        let malformed = dir.join("routes.txt");
+        std::fs::write(&malformed, "10.0.0.0/8 192.0.2.1\nnot-a-route 192.0.2.2\n").unwrap();
+
+        assert_eq!(exit_code(&["--lookup", missing.to_str().unwrap(), "10.1.1.1"]), 1);
+        assert_eq!(exit_code(&["--lookup", malformed.to_str().unwrap(), "10.1.1.1"]), 1);
+        assert_eq!(exit_code(&["--batch", missing.to_str().unwrap()]), 1);
+        assert_eq!(exit_code(&["--lookup", malformed.to_str().unwrap()]), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_generators_reject_json() {
+        assert_eq!(exit_code(&["--json", "--completions", "bash"]), 2);
+        assert_eq!(exit_code(&["--generate-man", "--json"]), 2);
+        assert_eq!(exit_code(&["--completions", "bash"]), 0);
+    }
+}
@@ -0,0 +1,274 @@
+use std::io::{self, BufRead, ErrorKind, Read, Write};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    KillToStart,
+    KillToEnd,
+    Interrupt,
+    EndOfFile,
+    Other,
+}
+
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<RawMode> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["-icanon", "-echo", "-isig", "-ixon", "min", "1", "time", "0"])?;
+        Some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+pub fn read_line(prompt: &str, history: &[String]) -> io::Result<Option<String>> {
+    if let Some(_raw) = RawMode::enable() {
+        return edit(&mut io::stdin().lock(), &mut io::stdout(), prompt, history);
+    }
+
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+fn edit<R: Read, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+    history: &[String],
+) -> io::Result<Option<String>> {
+    let mut chars: Vec<char> = Vec::new();
+    let mut cursor = 0;
+    let mut position = history.len();
+    let mut draft = Vec::new();
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    loop {
+        let Some(key) = read_key(input)? else {
+            if chars.is_empty() {
+                writeln!(output)?;
+                return Ok(None);
+            }
+            break;
+        };
+        match key {
+            Key::Enter => break,
+            Key::Char(c) => {
+                chars.insert(cursor, c);
+                cursor += 1;
+            },
+            Key::Backspace if cursor > 0 => {
+                cursor -= 1;
+                chars.remove(cursor);
+            },
+            Key::EndOfFile if chars.is_empty() => {
+                writeln!(output)?;
+                return Ok(None);
+            },
+            Key::Delete | Key::EndOfFile if cursor < chars.len() => {
+                chars.remove(cursor);
+            },
+            Key::Left if cursor > 0 => cursor -= 1,
+            Key::Right if cursor < chars.len() => cursor += 1,
+            Key::Home => cursor = 0,
+            Key::End => cursor = chars.len(),
+            Key::KillToStart => {
+                chars.drain(..cursor);
+                cursor = 0;
+            },
+            Key::KillToEnd => chars.truncate(cursor),
+            Key::Up if position > 0 => {
+                if position == history.len() {
+                    draft = chars.clone();
+                }
+                position -= 1;
+                chars = history[position].chars().collect();
+                cursor = chars.len();
+            },
+            Key::Down if position < history.len() => {
+                position += 1;
+                chars = match history.get(position) {
+                    Some(entry) => entry.chars().collect(),
+                    None => std::mem::take(&mut draft),
+                };
+                cursor = chars.len();
+            },
+            Key::Interrupt => {
+                writeln!(output, "^C")?;
+                chars.clear();
+                cursor = 0;
+                position = history.len();
+            },
+            _ => continue,
+        }
+        redraw(output, prompt, &chars, cursor)?;
+    }
+
+    writeln!(output)?;
+    Ok(Some(chars.into_iter().collect()))
+}
+
+fn redraw<W: Write>(output: &mut W, prompt: &str, chars: &[char], cursor: usize) -> io::Result<()> {
+    let line: String = chars.iter().collect();
+    write!(output, "\r\x1b[K{}{}", prompt, line)?;
+    if cursor < chars.len() {
+        write!(output, "\x1b[{}D", chars.len() - cursor)?;
+    }
+    output.flush()
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<Option<u8>> {
+    let mut byte = [0u8];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn read_key<R: Read>(input: &mut R) -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        127 | 8 => Key::Backspace,
+        1 => Key::Home,
+        2 => Key::Left,
+        3 => Key::Interrupt,
+        4 => Key::EndOfFile,
+        5 => Key::End,
+        6 => Key::Right,
+        11 => Key::KillToEnd,
+        14 => Key::Down,
+        16 => Key::Up,
+        21 => Key::KillToStart,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Other,
+        byte => read_char(input, byte)?,
+    };
+    Ok(Some(key))
+}
+
+fn read_escape<R: Read>(input: &mut R) -> io::Result<Key> {
+    if !matches!(read_byte(input)?, Some(b'[' | b'O')) {
+        return Ok(Key::Other);
+    }
+    let key = match read_byte(input)? {
+        Some(b'A') => Key::Up,
+        Some(b'B') => Key::Down,
+        Some(b'C') => Key::Right,
+        Some(b'D') => Key::Left,
+        Some(b'H') => Key::Home,
+        Some(b'F') => Key::End,
+        Some(digit) if digit.is_ascii_digit() => {
+            let mut code = vec![digit];
+            loop {
+                match read_byte(input)? {
+                    Some(b'~') => break,
+                    Some(byte) if byte.is_ascii_digit() || byte == b';' => code.push(byte),
+                    _ => return Ok(Key::Other),
+                }
+            }
+            match code.as_slice() {
+                b"1" | b"7" => Key::Home,
+                b"4" | b"8" => Key::End,
+                b"3" => Key::Delete,
+                _ => Key::Other,
+            }
+        },
+        _ => Key::Other,
+    };
+    Ok(key)
+}
+
+fn read_char<R: Read>(input: &mut R, first: u8) -> io::Result<Key> {
+    let length = match first {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return Ok(Key::Other),
+    };
+    let mut bytes = vec![first];
+    for _ in 1..length {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => return Ok(Key::Other),
+        }
+    }
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|text| text.chars().next())
+        .map_or(Key::Other, Key::Char))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_keys(keys: &[u8], history: &[String]) -> Option<String> {
+        edit(&mut &keys[..], &mut Vec::new(), "ip> ", history).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_editing() {
+        assert_eq!(type_keys(b"-cip 10.0.0.1\r", &[]).unwrap(), "-cip 10.0.0.1");
+        assert_eq!(type_keys(b"subnts\x1b[D\x1b[De\r", &[]).unwrap(), "subnets");
+        assert_eq!(type_keys(b"abc\x7f\x7fd\r", &[]).unwrap(), "ad");
+        assert_eq!(type_keys(b"xbc\x01\x1b[3~a\x05d\r", &[]).unwrap(), "abcd");
+        assert_eq!(type_keys(b"drop\x15keep\r", &[]).unwrap(), "keep");
+        assert_eq!(type_keys("r\u{e9}seau\r".as_bytes(), &[]).unwrap(), "r\u{e9}seau");
+        assert_eq!(type_keys(b"\x04", &[]), None);
+        assert_eq!(type_keys(b"", &[]), None);
+    }
+
+    #[test]
+    fn test_history_navigation() {
+        let history = vec!["display 10.0.0.0/8".to_string(), "subnets $_ 16".to_string()];
+        assert_eq!(type_keys(b"\x1b[A\r", &history).unwrap(), "subnets $_ 16");
+        assert_eq!(type_keys(b"\x1b[A\x1b[A\x1b[A\r", &history).unwrap(), "display 10.0.0.0/8");
+        assert_eq!(type_keys(b"draft\x1b[A\x1b[A\x1b[B\x1b[B\r", &history).unwrap(), "draft");
+        assert_eq!(type_keys(b"\x1bOA\x08\x0832\r", &history).unwrap(), "subnets $_ 32");
+    }
+}
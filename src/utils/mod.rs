@@ -1 +1,4 @@
-pub mod handle_arguments;
\ No newline at end of file
+pub mod batch;
+pub mod handle_arguments;
+pub mod line_editor;
+pub mod shell;
\ No newline at end of file
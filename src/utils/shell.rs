@@ -0,0 +1,285 @@
+use crate::libs::calc_ip::{CommandHelp, IpCalculatorError};
+use crate::libs::json::{Json, ToJson};
+use crate::libs::render::{self, render, Format, Style};
+use crate::utils::handle_arguments::{run_line, LineOptions, Outcome};
+use crate::utils::line_editor;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+const HISTORY_LIMIT: usize = 1000;
+
+pub struct Shell {
+    options: LineOptions,
+    variables: HashMap<String, String>,
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+}
+
+pub enum Reply {
+    Text(String),
+    Nothing,
+    Exit,
+}
+
+impl Shell {
+    pub fn new(options: LineOptions, history_path: Option<PathBuf>) -> Self {
+        let history = history_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let skip = history.len().saturating_sub(HISTORY_LIMIT);
+        let history: Vec<String> = history.into_iter().skip(skip).collect();
+        if let (true, Some(path)) = (skip > 0, &history_path) {
+            fs::write(path, history.join("\n") + "\n").ok();
+        }
+        Shell {
+            options,
+            variables: HashMap::new(),
+            history,
+            history_path,
+        }
+    }
+
+    pub fn eval(&mut self, line: &str) -> Result<Reply, IpCalculatorError> {
+        let line = self.recall(line.trim())?;
+        if line.is_empty() {
+            return Ok(Reply::Nothing);
+        }
+        self.remember(&line);
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens[0] {
+            "exit" | "quit" => Ok(Reply::Exit),
+            "help" => self.help(tokens.get(1).copied()),
+            "history" => Ok(Reply::Text(
+                self.history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, entry)| format!("{:>5}  {}", i + 1, entry))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            "let" => self.assign(&line),
+            _ => {
+                let mut args = Vec::new();
+                for token in &tokens {
+                    args.push(self.substitute(token)?);
+                }
+                self.run(args)
+            },
+        }
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    fn run(&mut self, mut args: Vec<String>) -> Result<Reply, IpCalculatorError> {
+        if !args[0].starts_with('-') {
+            if let Some(help) = CommandHelp::find_by_name_or_alias(&args[0]) {
+                match help.aliases.iter().find(|alias| alias.starts_with("--")) {
+                    Some(alias) => args[0] = alias.to_string(),
+                    None => {
+                        args.remove(0);
+                    },
+                }
+            }
+        }
+        if args.is_empty() {
+            return Err(IpCalculatorError::ArgumentsError(
+                "Missing arguments! Use 'help <command>' for usage.".to_string()
+            ));
+        }
+
+        let output = run_line(args, &self.options, "the shell")?;
+        for warning in render::warnings(&output) {
+            eprintln!("{}", warning);
+        }
+        self.variables.insert("_".to_string(), render::result_value(&output));
+        let format = if self.options.json { Format::Json } else { Format::Colored };
+        Ok(Reply::Text(render(&output, format)))
+    }
+
+    fn assign(&mut self, line: &str) -> Result<Reply, IpCalculatorError> {
+        let usage = || IpCalculatorError::ArgumentsError("Usage: let <name> = <value>".to_string());
+        let (name, value) = line["let".len()..].split_once('=').ok_or_else(usage)?;
+        let name = name.trim();
+        let value = value.trim();
+        if name.is_empty() || value.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(usage());
+        }
+
+        let mut words = Vec::new();
+        for word in value.split_whitespace() {
+            words.push(self.substitute(word)?);
+        }
+        let value = words.join(" ");
+        self.variables.insert(name.to_string(), value.clone());
+        Ok(Reply::Text(format!("{} = {}", name, value)))
+    }
+
+    fn help(&self, name: Option<&str>) -> Result<Reply, IpCalculatorError> {
+        let text = match name {
+            None if self.options.json => Json::array(CommandHelp::get_all()).to_string(),
+            None => render::command_list(Style::Colored),
+            Some(name) => match CommandHelp::find_by_name_or_alias(name) {
+                Some(help) if self.options.json => help.to_json().to_string(),
+                Some(help) => render::command_help(&help, Style::Colored),
+                None => return Err(IpCalculatorError::ArgumentsError(
+                    format!("Unknown command '{}'. Use help for a list of available commands.", name)
+                )),
+            },
+        };
+        Ok(Reply::Text(text))
+    }
+
+    fn substitute(&self, token: &str) -> Result<String, IpCalculatorError> {
+        let mut result = String::new();
+        let mut rest = token;
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let name = &after[..end];
+            if name.is_empty() {
+                result.push('$');
+            } else {
+                let value = self.variables.get(name).ok_or_else(|| IpCalculatorError::ArgumentsError(
+                    if name == "_" {
+                        "No previous result to use as $_".to_string()
+                    } else {
+                        format!("Undefined variable ${}", name)
+                    }
+                ))?;
+                result.push_str(value);
+            }
+            rest = &after[end..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    fn recall(&self, line: &str) -> Result<String, IpCalculatorError> {
+        let index = match line.strip_prefix('!') {
+            Some("!") => self.history.len(),
+            Some(number) if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) => {
+                number.parse().unwrap_or(0)
+            },
+            _ => return Ok(line.to_string()),
+        };
+        index
+            .checked_sub(1)
+            .and_then(|index| self.history.get(index))
+            .cloned()
+            .ok_or_else(|| IpCalculatorError::ArgumentsError(format!("{}: event not found", line)))
+    }
+
+    fn remember(&mut self, line: &str) {
+        if self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+
+        if let Some(path) = &self.history_path {
+            let saved = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| OpenOptions::new().create(true).append(true).open(path))
+                .and_then(|mut file| writeln!(file, "{}", line));
+            if let Err(e) = saved {
+                eprintln!("Warning: could not save history to {}: {}", path.display(), e);
+                self.history_path = None;
+            }
+        }
+    }
+}
+
+pub fn history_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(config.join("ip_calculator").join("history"))
+}
+
+fn read_stdin_line() -> io::Result<Option<String>> {
+    let mut line = String::new();
+    Ok((io::stdin().lock().read_line(&mut line)? > 0).then_some(line))
+}
+
+pub fn run_shell(options: LineOptions) -> Result<Outcome, IpCalculatorError> {
+    let interactive = io::stdin().is_terminal();
+    let mut shell = Shell::new(options, history_path());
+
+    loop {
+        let line = if interactive {
+            line_editor::read_line("ip> ", shell.history())
+        } else {
+            read_stdin_line()
+        };
+        let line = line.map_err(|e| IpCalculatorError::InputError(
+            format!("Failed to read from stdin: {}", e)
+        ))?;
+        let Some(line) = line else {
+            break;
+        };
+
+        match shell.eval(&line) {
+            Ok(Reply::Text(text)) => println!("{}", text),
+            Ok(Reply::Nothing) => {},
+            Ok(Reply::Exit) => break,
+            Err(e) => eprintln!("{}", render::render_error(&e, Style::Colored)),
+        }
+    }
+    Ok(Outcome { text: String::new(), exit_code: 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell() -> Shell {
+        Shell::new(LineOptions { wildcard: false, verbose: false, json: false, columns: None }, None)
+    }
+
+    #[test]
+    fn test_variables_and_last_result() {
+        let mut shell = shell();
+        assert!(matches!(shell.eval("let core = 10.0.0.0/16").unwrap(), Reply::Text(text) if text == "core = 10.0.0.0/16"));
+        assert!(matches!(shell.eval("get-subnet $core 24 5").unwrap(), Reply::Text(_)));
+        assert_eq!(shell.variable("_"), Some("10.0.5.0/24"));
+
+        shell.eval("display $_").unwrap();
+        assert_eq!(shell.variable("_"), Some("10.0.5.0/24"));
+        shell.eval("subnets $core 18").unwrap();
+        assert_eq!(shell.variable("_"), Some("10.0.0.0/18,10.0.64.0/18,10.0.128.0/18,10.0.192.0/18"));
+    }
+
+    #[test]
+    fn test_errors_and_history_recall() {
+        let mut shell = shell();
+        assert!(shell.eval("subnets $missing 24").is_err());
+        assert!(shell.eval("check-ip 300.1.1.1").is_err());
+        assert!(shell.eval("--batch").is_err());
+        assert!(matches!(shell.eval("help subnets").unwrap(), Reply::Text(_)));
+        assert!(matches!(shell.eval("quit").unwrap(), Reply::Exit));
+
+        shell.eval("-cip 10.0.0.1").unwrap();
+        shell.eval("!!").unwrap();
+        assert_eq!(shell.variable("_"), Some("10.0.0.1"));
+        assert!(shell.eval("!99").is_err());
+    }
+}